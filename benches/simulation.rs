@@ -50,7 +50,7 @@ fn simulation_inter_test() {
                     }
 
                     let sim = graph1.get_simulation_inter(&graph2);
-                    let has_sim=  StandardLabeledGraph::has_simulation(sim);
+                    let has_sim=  StandardLabeledGraph::has_simulation(&sim);
             }
         }
     }
@@ -102,7 +102,7 @@ fn simulation_native_test() {
                     }
 
                     let sim = graph1.get_simulation_native(&graph2);
-                    let has_sim=  StandardLabeledGraph::has_simulation(sim);
+                    let has_sim=  StandardLabeledGraph::has_simulation(&sim);
             }
         }
     }
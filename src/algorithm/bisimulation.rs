@@ -0,0 +1,263 @@
+use graph_base::interfaces::graph::{Adjacency, AdjacencyInv, Directed, Graph};
+use graph_base::interfaces::labeled::Labeled;
+
+use std::collections::{HashMap, HashSet};
+
+pub trait Bisimulation<'a> {
+    type Node: 'a;
+
+    /// The coarsest stable partition of `self` that respects `label_same`, i.e. its bisimulation equivalence classes.
+    fn bisimulation_partition(&'a self) -> Vec<HashSet<&'a Self::Node>>;
+
+    /// Maps every node of `self` to the nodes of `other` that are bisimilar to it.
+    fn get_bisimulation(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+}
+
+impl<'a, T> Bisimulation<'a> for T
+where
+    T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> + Labeled<'a> + Directed,
+    T::Node: 'a, T::Edge: 'a,
+{
+    type Node = T::Node;
+
+    fn bisimulation_partition(&'a self) -> Vec<HashSet<&'a Self::Node>> {
+        let nodes: Vec<&T::Node> = self.nodes().collect();
+        let index: HashMap<&T::Node, usize> = nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let edges: Vec<(usize, usize)> = self.get_edges_pair().map(|(v, v_prime)| (index[v], index[v_prime])).collect();
+        let labels = label_classes(self, &nodes);
+
+        let block_of = paige_tarjan(nodes.len(), &edges, &labels);
+
+        let mut classes: HashMap<usize, HashSet<&T::Node>> = HashMap::new();
+        for (i, v) in nodes.into_iter().enumerate() {
+            classes.entry(block_of[i]).or_default().insert(v);
+        }
+        classes.into_values().collect()
+    }
+
+    fn get_bisimulation(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        // Both graphs are refined together as their disjoint union: nodes of `self` come first, then nodes of `other`.
+        let self_nodes: Vec<&T::Node> = self.nodes().collect();
+        let other_nodes: Vec<&T::Node> = other.nodes().collect();
+        let offset = self_nodes.len();
+        let self_index: HashMap<&T::Node, usize> = self_nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let other_index: HashMap<&T::Node, usize> = other_nodes.iter().enumerate().map(|(i, &v)| (v, offset + i)).collect();
+
+        let edges: Vec<(usize, usize)> = self.get_edges_pair().map(|(v, v_prime)| (self_index[v], self_index[v_prime]))
+            .chain(other.get_edges_pair().map(|(v, v_prime)| (other_index[v], other_index[v_prime])))
+            .collect();
+        let nodes: Vec<&T::Node> = self_nodes.iter().chain(other_nodes.iter()).copied().collect();
+        let labels = label_classes(self, &nodes);
+
+        let block_of = paige_tarjan(nodes.len(), &edges, &labels);
+
+        let mut other_blocks: HashMap<usize, HashSet<&T::Node>> = HashMap::new();
+        for (i, &v) in other_nodes.iter().enumerate() {
+            other_blocks.entry(block_of[offset + i]).or_default().insert(v);
+        }
+        self_nodes.iter().enumerate().map(|(i, &u)| {
+            (u, other_blocks.get(&block_of[i]).cloned().unwrap_or_default())
+        }).collect()
+    }
+}
+
+/// Groups `nodes` by `label_same`, returning the group of every node.
+fn label_classes<'a, T: Labeled<'a>>(graph: &T, nodes: &[&'a T::Node]) -> Vec<usize> {
+    let mut representatives: Vec<&T::Node> = Vec::new();
+    nodes.iter().map(|&v| {
+        match representatives.iter().position(|&r| graph.label_same(r, v)) {
+            Some(class) => class,
+            None => {
+                representatives.push(v);
+                representatives.len() - 1
+            }
+        }
+    }).collect()
+}
+
+struct Block {
+    begin: usize,
+    end: usize,
+    marked: usize,
+    compound: usize,
+    idx_in_compound: usize,
+}
+
+struct Compound {
+    blocks: Vec<usize>,
+    queued: bool,
+}
+
+/// The blocks of Q laid out as ranges of `elems`, and the compound blocks of X grouping them.
+struct Partition {
+    elems: Vec<usize>,
+    pos: Vec<usize>,
+    block_of: Vec<usize>,
+    blocks: Vec<Block>,
+    compounds: Vec<Compound>,
+    queue: Vec<usize>,
+}
+
+impl Partition {
+    fn size(&self, b: usize) -> usize {
+        self.blocks[b].end - self.blocks[b].begin
+    }
+
+    fn enqueue(&mut self, c: usize) {
+        if self.compounds[c].blocks.len() >= 2 && !self.compounds[c].queued {
+            self.compounds[c].queued = true;
+            self.queue.push(c);
+        }
+    }
+
+    /// Splits every block into its nodes in `marked` and the rest. `marked` must not contain duplicates.
+    fn split(&mut self, marked: &[usize]) {
+        let mut touched = Vec::new();
+        for &x in marked {
+            let b = self.block_of[x];
+            if self.blocks[b].marked == 0 {
+                touched.push(b);
+            }
+            let target = self.blocks[b].begin + self.blocks[b].marked;
+            let (x_pos, y) = (self.pos[x], self.elems[target]);
+            self.elems.swap(x_pos, target);
+            self.pos[x] = target;
+            self.pos[y] = x_pos;
+            self.blocks[b].marked += 1;
+        }
+
+        for b in touched {
+            let marked = std::mem::take(&mut self.blocks[b].marked);
+            if marked == self.size(b) {
+                continue;
+            }
+            let (begin, compound) = (self.blocks[b].begin, self.blocks[b].compound);
+            let new_block = self.blocks.len();
+            self.blocks.push(Block {
+                begin,
+                end: begin + marked,
+                marked: 0,
+                compound,
+                idx_in_compound: self.compounds[compound].blocks.len(),
+            });
+            self.compounds[compound].blocks.push(new_block);
+            self.blocks[b].begin += marked;
+            for i in begin..begin + marked {
+                self.block_of[self.elems[i]] = new_block;
+            }
+            self.enqueue(compound);
+        }
+    }
+
+    /// Moves block `b` out of its compound block into a new compound block of its own.
+    fn detach(&mut self, b: usize) {
+        let (compound, idx) = (self.blocks[b].compound, self.blocks[b].idx_in_compound);
+        self.compounds[compound].blocks.swap_remove(idx);
+        if let Some(&moved) = self.compounds[compound].blocks.get(idx) {
+            self.blocks[moved].idx_in_compound = idx;
+        }
+        self.blocks[b].compound = self.compounds.len();
+        self.blocks[b].idx_in_compound = 0;
+        self.compounds.push(Compound { blocks: vec![b], queued: false });
+        self.enqueue(compound);
+    }
+}
+
+/// Paige–Tarjan relational coarsest partition over nodes `0..n`, starting from the partition given by `initial`.
+///
+/// Returns the block of every node in the coarsest partition that refines `initial` and is stable with
+/// respect to the successor relation, i.e. two nodes share a block iff they are bisimilar.
+fn paige_tarjan(n: usize, edges: &[(usize, usize)], initial: &[usize]) -> Vec<usize> {
+    let mut pred: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    let mut out_degree = vec![0; n];
+    for (e, &(x, y)) in edges.iter().enumerate() {
+        pred[y].push((x, e));
+        out_degree[x] += 1;
+    }
+
+    // count(x, S) = |E(x) ∩ S| for the compound block S, shared by every edge (x, y) with y ∈ S.
+    let mut counts: Vec<usize> = Vec::new();
+    let mut edge_count = vec![0; edges.len()];
+    let mut record_of = vec![usize::MAX; n];
+    for (e, &(x, _)) in edges.iter().enumerate() {
+        if record_of[x] == usize::MAX {
+            record_of[x] = counts.len();
+            counts.push(out_degree[x]);
+        }
+        edge_count[e] = record_of[x];
+    }
+
+    // The initial partition, already stable with respect to the universe: nodes with and without successors are split.
+    let mut elems: Vec<usize> = (0..n).collect();
+    elems.sort_by_key(|&x| (initial[x], out_degree[x] != 0));
+    let mut partition = Partition {
+        pos: vec![0; n],
+        block_of: vec![0; n],
+        elems,
+        blocks: Vec::new(),
+        compounds: vec![Compound { blocks: Vec::new(), queued: false }],
+        queue: Vec::new(),
+    };
+    let key = |x: usize| (initial[x], out_degree[x] != 0);
+    for i in 0..n {
+        let x = partition.elems[i];
+        partition.pos[x] = i;
+        if i == 0 || key(partition.elems[i - 1]) != key(x) {
+            let idx_in_compound = partition.compounds[0].blocks.len();
+            partition.compounds[0].blocks.push(partition.blocks.len());
+            partition.blocks.push(Block { begin: i, end: i, marked: 0, compound: 0, idx_in_compound });
+        }
+        let b = partition.blocks.len() - 1;
+        partition.blocks[b].end = i + 1;
+        partition.block_of[x] = b;
+    }
+    partition.enqueue(0);
+
+    let mut count_b = vec![usize::MAX; n];
+    let mut count_s = vec![usize::MAX; n];
+    while let Some(s) = partition.queue.pop() {
+        partition.compounds[s].queued = false;
+        if partition.compounds[s].blocks.len() < 2 {
+            continue;
+        }
+
+        // Refine with the smaller of two blocks B of S, splitting S into B and S \ B.
+        let (b0, b1) = (partition.compounds[s].blocks[0], partition.compounds[s].blocks[1]);
+        let b = if partition.size(b0) <= partition.size(b1) { b0 } else { b1 };
+        partition.detach(b);
+        let b_nodes: Vec<usize> = partition.elems[partition.blocks[b].begin..partition.blocks[b].end].to_vec();
+
+        // E^{-1}(B), with count(x, B) for each of its nodes.
+        let mut pre_b = Vec::new();
+        for &y in &b_nodes {
+            for &(x, e) in &pred[y] {
+                if count_b[x] == usize::MAX {
+                    count_b[x] = counts.len();
+                    counts.push(0);
+                    count_s[x] = edge_count[e];
+                    pre_b.push(x);
+                }
+                counts[count_b[x]] += 1;
+            }
+        }
+
+        // Split with respect to B, then with respect to S \ B: nodes of E^{-1}(B) with no edge into S \ B.
+        partition.split(&pre_b);
+        let only_b: Vec<usize> = pre_b.iter().copied().filter(|&x| counts[count_b[x]] == counts[count_s[x]]).collect();
+        partition.split(&only_b);
+
+        // Edges into B now count towards count(x, B) instead of count(x, S).
+        for &y in &b_nodes {
+            for &(x, e) in &pred[y] {
+                counts[edge_count[e]] -= 1;
+                edge_count[e] = count_b[x];
+            }
+        }
+        for x in pre_b {
+            count_b[x] = usize::MAX;
+            count_s[x] = usize::MAX;
+        }
+    }
+
+    partition.block_of
+}
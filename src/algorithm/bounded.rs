@@ -3,23 +3,110 @@ use graph_base::interfaces::labeled::Labeled;
 
 use std::collections::{HashSet, HashMap};
 
+use crate::algorithm::explain::{Reason, SimulationExplanation};
+use crate::algorithm::dense::{label_candidates, to_relation, BitSet, DenseGraph};
+use crate::algorithm::reach::{FullReach, Reach, TruncatedReach};
+use crate::utils::parallel::{par_filter, MaybeSync};
+
+// anc or dec of `get_bounded_simulation_matrix`, by (bound, pattern node, data node).
+type BoundIndex<'n, N> = HashMap<(Bound, &'n N, &'n N), HashSet<&'n N>>;
+
 pub trait BoundedSimulation<'a> {
     type Node: 'a;
+    /// `get_bounded_simulation_with` and `Reachability::OnDemand`, which only explores the data graph up to the bounds of the pattern.
     fn get_bounded_simulation(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
+    /// The original refinement on a hashed all-pairs distance matrix of `other`, with O(n²) memory and time.
+    /// Kept as the reference the other variants are checked against.
+    fn get_bounded_simulation_matrix(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
+    /// `get_bounded_simulation_matrix` on dense indices: anc and dec become bounded reachability bitsets
+    /// intersected with the label candidates, instead of a hashed distance matrix.
+    fn get_bounded_simulation_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
+    /// `get_bounded_simulation_dense`, answering the distance queries on the data graph as `reachability` says.
+    fn get_bounded_simulation_with(&'a self, other: &'a Self, reachability: Reachability) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
+    /// `get_bounded_simulation`, recording for every pair that is not in the result why it was removed.
+    fn get_bounded_simulation_explained(&'a self, other: &'a Self) -> SimulationExplanation<'a, Self::Node>;
+}
+
+/// How the dense bounded simulation finds the data nodes within a bound of each other. All modes give the same result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    /// Reachability bitsets of every data node for every bound of the pattern, O(n²) bits per bound.
+    Full,
+    /// BFS truncated at the largest bound of the pattern, only from the queried nodes and cached per source.
+    OnDemand,
+    /// `OnDemand`, deciding most queries first on the exact distances from and to this many landmarks.
+    Landmarks(usize),
 }
 
 pub trait Bounded<'a>: Graph<'a> {
+    /// The greatest length of a data path matching the pattern edge (u, v).
     fn get_bound(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> usize;
+
+    /// The lengths of the data paths matching the pattern edge (u, v), the ones up to `get_bound(u, v)` unless overridden.
+    fn get_bound_range(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> Bound {
+        Bound::AtMost(self.get_bound(u, v))
+    }
+}
+
+/// The lengths of the data paths that match a pattern edge. A path may repeat nodes, and a node is
+/// the end of the path of length 0 from itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bound {
+    /// At most k edges.
+    AtMost(usize),
+    /// Exactly k edges.
+    Exactly(usize),
+    /// From lo to hi edges, both included.
+    Range(usize, usize),
+    /// Any number of edges, i.e. plain reachability.
+    Unbounded,
+}
+
+impl Bound {
+    /// The least and the greatest length, `None` when there is no greatest one.
+    pub fn limits(&self) -> (usize, Option<usize>) {
+        match *self {
+            Bound::AtMost(k) => (0, Some(k)),
+            Bound::Exactly(k) => (k, Some(k)),
+            Bound::Range(lo, hi) => (lo, Some(hi)),
+            Bound::Unbounded => (0, None),
+        }
+    }
+}
+
+impl From<usize> for Bound {
+    fn from(k: usize) -> Self {
+        Bound::AtMost(k)
+    }
+}
+
+impl std::fmt::Display for Bound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bound::AtMost(k) => write!(f, "at most {}", k),
+            Bound::Exactly(k) => write!(f, "exactly {}", k),
+            Bound::Range(lo, hi) => write!(f, "{} to {}", lo, hi),
+            Bound::Unbounded => write!(f, "any number of"),
+        }
+    }
 }
 
 impl<'a, 'b, T> BoundedSimulation<'a> for T 
 where
-    T: Graph<'a> + Bounded<'a> + Degree<'a> + Labeled<'a> + Adjacency<'a> + Degree<'a> + AdjacencyInv<'a> + Directed + 'b,
-    T::Node: 'a, T::Edge: 'a,
+    T: Graph<'a> + Bounded<'a> + Degree<'a> + Labeled<'a> + Adjacency<'a> + Degree<'a> + AdjacencyInv<'a> + Directed + MaybeSync + 'b,
+    T::Node: 'a + MaybeSync, T::Edge: 'a,
 {
     type Node = T::Node;
 
     fn get_bounded_simulation(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        bounded_simulation_dense(self, other, Reachability::OnDemand, None)
+    }
+
+    fn get_bounded_simulation_matrix(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
 
         let adj_self = self.get_adj();
         let adj_other = other.get_adj();
@@ -34,7 +121,7 @@ where
         // dec(get_bound(u, u_prime), u_prime, v) that returns v_prime in dec if:
         // 1. label_same(u_prime, v_prime)
         // 2. len(v/.../v_prime) <= get_bound(u, u_prime)
-        // they are HashMap<(usize, &Node, &Node), HashSet<&Node>>
+        // they are HashMap<(Bound, &Node, &Node), HashSet<&Node>>
 
         // We firstly compute the distance matrix M of (V_other, E_other)
         let mut distance: HashMap<(&T::Node, &T::Node), usize> = HashMap::new();
@@ -54,27 +141,51 @@ where
             }
         }
         
-        let mut anc: HashMap<(usize, &T::Node, &T::Node), HashSet<&T::Node>> = HashMap::new();
-        let mut dec: HashMap<(usize, &T::Node, &T::Node), HashSet<&T::Node>> = HashMap::new();
+        // A bound with a least length is not decided by the distance: walks(bound, v) is the set of the
+        // ends of the paths v/.../v' whose length is within bound.
+        let mut walks: HashMap<(Bound, &T::Node), HashSet<&T::Node>> = HashMap::new();
+        let walk_bounds: HashSet<Bound> = self.get_edges_pair()
+            .map(|(u, u_prime)| self.get_bound_range(u, u_prime))
+            .filter(|bound| bound.limits().0 > 0)
+            .collect();
+        for bound in walk_bounds {
+            let (lo, hi) = bound.limits();
+            let hi = hi.expect("A bound with a least length has a greatest one");
+            for v in other.nodes() {
+                let mut ends: HashSet<&T::Node> = HashSet::new();
+                let mut layer: HashSet<&T::Node> = HashSet::from([v]);
+                for length in 1..=hi {
+                    layer = layer.iter().flat_map(|w| other.get_post(&adj_other, w)).collect();
+                    if length >= lo {
+                        ends.extend(layer.iter().copied());
+                    }
+                }
+                walks.insert((bound, v), ends);
+            }
+        }
+        // Whether a path v/.../v' has a length within bound.
+        let within = |bound: Bound, v: &'a T::Node, v_prime: &'a T::Node| match bound.limits() {
+            (0, hi) => distance.get(&(v, v_prime)).is_some_and(|&dist| hi.is_none_or(|hi| dist <= hi)),
+            _ => walks[&(bound, v)].contains(v_prime),
+        };
+
+        let mut anc: BoundIndex<T::Node> = HashMap::new();
+        let mut dec: BoundIndex<T::Node> = HashMap::new();
         
         
         // Then we compute anc and dec based on distance matrix
 
         // compute anc
-        // anc(bound, u_prime, v) := {v_prime | label_same(u_prime, v_prime) and distance(v_prime, v) <= bound}
+        // anc(bound, u_prime, v) := {v_prime | label_same(u_prime, v_prime) and len(v_prime/.../v) within bound}
         // where u_prime is node from self, v_prime and v are nodes from other
         for u_prime in self.nodes() {
             for u in self.get_post(&adj_self, u_prime) {
-                let bound = self.get_bound(u_prime, u);
+                let bound = self.get_bound_range(u_prime, u);
                 for v in other.nodes() {
                     let mut anc_set: HashSet<&T::Node> = HashSet::new();
                     for v_prime in other.nodes() {
-                        if self.label_same(u_prime, v_prime) {
-                            if let Some(&dist) = distance.get(&(v_prime, v)) {
-                                if dist <= bound {
-                                    anc_set.insert(v_prime);
-                                }
-                            }
+                        if self.label_same(u_prime, v_prime) && within(bound, v_prime, v) {
+                            anc_set.insert(v_prime);
                         }
                     }
                     anc.insert((bound, u_prime, v), anc_set);
@@ -83,21 +194,17 @@ where
         }
 
         // compute dec
-        // dec(bound, u_prime, v) := {v_prime | label_same(u_prime, v_prime) and distance(v, v_prime) <= bound}
+        // dec(bound, u_prime, v) := {v_prime | label_same(u_prime, v_prime) and len(v/.../v_prime) within bound}
         // where u_prime is node from self, v_prime and v are nodes from other
         // We need to compute dec for all possible (u', u) pairs to get all required bounds
         for u in self.nodes() {
             for u_prime in self.get_post(&adj_self, u) {
-                let bound = self.get_bound(u, u_prime);
+                let bound = self.get_bound_range(u, u_prime);
                 for v in other.nodes() {
                     let mut dec_set: HashSet<&T::Node> = HashSet::new();
                     for v_prime in other.nodes() {
-                        if self.label_same(u_prime, v_prime) {
-                            if let Some(&dist) = distance.get(&(v, v_prime)) {  
-                                if dist <= bound {
-                                    dec_set.insert(v_prime);
-                                }
-                            }
+                        if self.label_same(u_prime, v_prime) && within(bound, v, v_prime) {
+                            dec_set.insert(v_prime);
                         }
                     }
                     dec.insert((bound, u_prime, v), dec_set);
@@ -111,18 +218,11 @@ where
         // sim(u) := {v | v in V_other and label_same(u, v) and out_degree(v) != 0 if out_degree(u) != 0}
         let mut sim = HashMap::new();
         for u in self.nodes() {
-            let mut candidates: HashSet<&'a T::Node> = HashSet::new();
-            for v in other.nodes() {
-                if self.label_same(&u, v) {
-                    if self.out_degree(&self_out_degree,&u) != 0 {
-                        if other.out_degree(&other_out_degree,&v) != 0 {
-                            candidates.insert(v);
-                        }
-                    } else {
-                        candidates.insert(v);
-                    }
-                }
-            }
+            let candidates: HashSet<&'a T::Node> = if self.out_degree(&self_out_degree, u) != 0 {
+                par_filter(other.nodes(), |v| self.label_same(u, v) && other.out_degree(&other_out_degree, v) != 0)
+            } else {
+                par_filter(other.nodes(), |v| self.label_same(u, v))
+            };
             sim.insert(u, candidates);
         }
 
@@ -144,7 +244,7 @@ where
                         continue;  // 这个 u_prime 无法排除 v，检查下一个 u_prime
                     }
                     
-                    let bound = self.get_bound(&u_prime, &u);
+                    let bound = self.get_bound_range(u_prime, u);
                     // 检查：是否存在 v' 满足条件
                     // (1) v' in sim(u)
                     // (2) label_same(u_prime, v) - 已经满足（见上面的检查）
@@ -213,7 +313,7 @@ where
                     let presim_u_prime = presim.get(&u_prime).unwrap().clone();
                     
                     for u_double_prime in u_double_primes {
-                        let bound = self.get_bound(&u_double_prime, &u_prime);
+                        let bound = self.get_bound_range(u_double_prime, u_prime);
                         
                         if let Some(anc_set) = anc.get(&(bound, &u_double_prime, &z)) {
                             // 收集 anc_set 到临时变量
@@ -249,4 +349,149 @@ where
 
         sim
     }
-}
\ No newline at end of file
+
+    fn get_bounded_simulation_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        bounded_simulation_dense(self, other, Reachability::Full, None)
+    }
+
+    fn get_bounded_simulation_with(&'a self, other: &'a Self, reachability: Reachability) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        bounded_simulation_dense(self, other, reachability, None)
+    }
+
+    fn get_bounded_simulation_explained(&'a self, other: &'a Self) -> SimulationExplanation<'a, Self::Node> {
+        let mut explanation = SimulationExplanation::new();
+        explanation.relation = bounded_simulation_dense(self, other, Reachability::Full, Some(&mut explanation));
+        explanation
+    }
+}
+
+/// The dense refinement of `get_bounded_simulation_matrix`. With an `explanation`, every pair left out of the
+/// initial candidates or removed later is recorded in it, together with the pattern edge, its bound and
+/// the removed pair that put the node into premv.
+fn bounded_simulation_dense<'a, T>(
+    graph: &'a T,
+    other: &'a T,
+    reachability: Reachability,
+    mut explanation: Option<&mut SimulationExplanation<'a, T::Node>>,
+) -> HashMap<&'a T::Node, HashSet<&'a T::Node>>
+where T: Bounded<'a> + Labeled<'a> {
+    let (pattern, data) = (DenseGraph::new(graph), DenseGraph::new(other));
+    let label = label_candidates(graph, &pattern, &data);
+    let bound = |u_prime: usize, u: usize| graph.get_bound_range(pattern.nodes[u_prime], pattern.nodes[u]);
+
+    // anc(bound, u', v) = label(u') ∩ ancestors of v within bound, and dec(bound, u', v) = label(u') ∩ descendants
+    // of v within bound. As in get_bounded_simulation_matrix, anc is defined for the sources u' of pattern edges
+    // and dec for their targets, keyed by the bound of the edge.
+    let mut anc_keys: HashSet<(Bound, usize)> = HashSet::new();
+    let mut dec_keys: HashSet<(Bound, usize)> = HashSet::new();
+    for u in 0..pattern.len() {
+        for &u_prime in pattern.post.neighbors(u) {
+            let b = bound(u, u_prime as usize);
+            anc_keys.insert((b, u));
+            dec_keys.insert((b, u_prime as usize));
+        }
+    }
+    let bounds = anc_keys.iter().map(|&(b, _)| b);
+    let reach: Box<dyn Reach + '_> = match reachability {
+        Reachability::Full => Box::new(FullReach::new(&data, bounds)),
+        Reachability::OnDemand => Box::new(TruncatedReach::new(&data, depth(bounds), None)),
+        Reachability::Landmarks(k) => Box::new(TruncatedReach::new(&data, depth(bounds), Some(k))),
+    };
+
+    // sim(u) := {v | label_same(u, v) and out_degree(v) != 0 if out_degree(u) != 0}
+    let mut sim = label.clone();
+    for (u, sim_u) in sim.iter_mut().enumerate() {
+        if pattern.post.degree(u) != 0 {
+            for v in (0..data.len()).filter(|&v| data.post.degree(v) == 0) {
+                sim_u.remove(v);
+            }
+        }
+    }
+    if let Some(explanation) = explanation.as_deref_mut() {
+        for (u, sim_u) in sim.iter().enumerate() {
+            for v in (0..data.len()).filter(|&v| !sim_u.contains(v)) {
+                let reason = if label[u].contains(v) { Reason::DeadEnd } else { Reason::LabelMismatch };
+                explanation.record(pattern.nodes[u], data.nodes[v], reason, 0, 0);
+            }
+        }
+    }
+    // cause[(u, v)] is the removed pair that put v into presim(u), if any.
+    let mut cause: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+
+    // presim(u) := {v ∈ sim(u) | out_degree(v) != 0 and no (u', u) ∈ E_self with label_same(u', v) and dec(bound, u', v) ∩ sim(u) != ∅}
+    let mut presim: Vec<BitSet> = (0..pattern.len()).map(|u| {
+        let mut presim_u = BitSet::new(data.len());
+        for v in sim[u].iter().filter(|&v| data.post.degree(v) != 0) {
+            let excluded = pattern.pre.neighbors(u).iter().any(|&u_prime| {
+                let (u_prime, b) = (u_prime as usize, bound(u_prime as usize, u));
+                label[u_prime].contains(v)
+                    && dec_keys.contains(&(b, u_prime))
+                    && reach.descendants_meet(v, b, &label[u_prime], &sim[u])
+            });
+            if !excluded {
+                presim_u.insert(v);
+            }
+        }
+        presim_u
+    }).collect();
+
+    let (mut round, mut step) = (0, 0);
+    while let Some(u) = (0..pattern.len()).find(|&u| !presim[u].is_empty()) {
+        round += 1;
+        let premv_u = presim[u].clone();
+        for &u_prime in pattern.pre.neighbors(u) {
+            let u_prime = u_prime as usize;
+            let mut to_remove = premv_u.clone();
+            to_remove.intersect_with(&sim[u_prime]);
+
+            for z in to_remove.iter() {
+                sim[u_prime].remove(z);
+                step += 1;
+                if let Some(explanation) = explanation.as_deref_mut() {
+                    let reason = Reason::Unmatched {
+                        child: pattern.nodes[u],
+                        bound: Some(bound(u_prime, u)),
+                        cause: cause.get(&(u, z)).map(|&(c_u, c_v)| (pattern.nodes[c_u], data.nodes[c_v])),
+                    };
+                    explanation.record(pattern.nodes[u_prime], data.nodes[z], reason, round, step);
+                }
+                if sim[u_prime].is_empty() {
+                    if let Some(explanation) = explanation {
+                        explanation.emptied = Some((pattern.nodes[u_prime], step));
+                    }
+                    return HashMap::new();
+                }
+
+                // z' ∈ anc(bound, u'', z) \ presim(u') with dec(bound, u', z') ∩ sim(u') = ∅ joins presim(u'').
+                let presim_u_prime = presim[u_prime].clone();
+                let mut updates: Vec<(usize, usize)> = Vec::new();
+                for &u_double_prime in pattern.pre.neighbors(u_prime) {
+                    let u_double_prime = u_double_prime as usize;
+                    let b = bound(u_double_prime, u_prime);
+                    if !anc_keys.contains(&(b, u_double_prime)) {
+                        continue;
+                    }
+                    for z_prime in reach.ancestors_in(z, b, &label[u_double_prime]) {
+                        if !presim_u_prime.contains(z_prime)
+                            && dec_keys.contains(&(b, u_prime))
+                            && !reach.descendants_meet(z_prime, b, &label[u_prime], &sim[u_prime]) {
+                            updates.push((u_double_prime, z_prime));
+                        }
+                    }
+                }
+                for (u_double_prime, z_prime) in updates {
+                    presim[u_double_prime].insert(z_prime);
+                    cause.insert((u_double_prime, z_prime), (u_prime, z));
+                }
+            }
+        }
+        presim[u].clear();
+    }
+
+    to_relation(&pattern, &data, &sim)
+}
+
+/// The greatest length of the bounds, `usize::MAX` for `Bound::Unbounded`.
+fn depth(bounds: impl Iterator<Item = Bound>) -> usize {
+    bounds.map(|bound| bound.limits().1.unwrap_or(usize::MAX)).max().unwrap_or(0)
+}
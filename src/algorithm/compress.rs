@@ -0,0 +1,101 @@
+use graph_base::impls::standard::{LabelNode, StandardLabeledGraph};
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_base::interfaces::labeled::{Label, Labeled};
+
+use std::collections::{HashMap, HashSet};
+
+use crate::algorithm::bisimulation::Bisimulation;
+
+/// A data graph with its bisimilar nodes merged: one node per class of `bisimulation_partition`, with the label
+/// of the class, and an edge between two classes if a node of the first has an edge to a node of the second.
+///
+/// A node of a data graph is in sim(u) iff its class is, for the simulations that only follow the successors
+/// (`get_simulation_inter`, `_hhk`, `_native` and their dense variants), so these can be run on the much smaller
+/// compressed graph instead. Dual and strong simulation also follow the predecessors, which the classes do not respect.
+pub struct CompressedGraph<'a> {
+    graph: StandardLabeledGraph,
+    // classes[c]: the nodes of the data graph in the class of the compressed node with id c.
+    classes: Vec<Vec<&'a LabelNode<String>>>,
+    class_of: HashMap<&'a LabelNode<String>, usize>,
+}
+
+pub trait Compression {
+    /// Merges the bisimilar nodes of `self`. The classes are numbered in the order of their first node in `nodes()`.
+    fn compress(&self) -> CompressedGraph<'_>;
+}
+
+impl Compression for StandardLabeledGraph {
+    fn compress(&self) -> CompressedGraph<'_> {
+        let partition = self.bisimulation_partition();
+        let block_of: HashMap<&LabelNode<String>, usize> = partition.iter().enumerate()
+            .flat_map(|(b, block)| block.iter().map(move |&v| (v, b)))
+            .collect();
+
+        // Renumbers the blocks in the order of the nodes, so that the compressed graph does not depend on hashing.
+        let mut class_of_block: HashMap<usize, usize> = HashMap::new();
+        let mut classes: Vec<Vec<&LabelNode<String>>> = Vec::new();
+        let mut class_of: HashMap<&LabelNode<String>, usize> = HashMap::new();
+        let mut graph = StandardLabeledGraph::new();
+        for v in self.nodes() {
+            let c = *class_of_block.entry(block_of[v]).or_insert_with(|| {
+                graph.add_node(classes.len() as u64, self.get_label(v).label().to_string());
+                classes.push(Vec::new());
+                classes.len() - 1
+            });
+            classes[c].push(v);
+            class_of.insert(v, c);
+        }
+
+        let mut edges: HashSet<(usize, usize)> = HashSet::new();
+        for (v, v_prime) in self.get_edges_pair() {
+            let edge = (class_of[v], class_of[v_prime]);
+            if edges.insert(edge) {
+                graph.add_edge(edge.0 as u64, edge.1 as u64);
+            }
+        }
+        CompressedGraph { graph, classes, class_of }
+    }
+}
+
+impl<'a> CompressedGraph<'a> {
+    /// The compressed graph, whose node ids are the class numbers.
+    pub fn graph(&self) -> &StandardLabeledGraph {
+        &self.graph
+    }
+
+    /// The class number of a node of the data graph.
+    pub fn class_of(&self, v: &LabelNode<String>) -> usize {
+        self.class_of[v]
+    }
+
+    /// The nodes of the data graph merged into the compressed node `class`.
+    pub fn members(&self, class: &LabelNode<String>) -> &[&'a LabelNode<String>] {
+        &self.classes[class.id()]
+    }
+
+    /// The number of classes.
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Replaces every compressed node of `sim` by the nodes of its class.
+    pub fn decompress<'p>(&self, sim: HashMap<&'p LabelNode<String>, HashSet<&LabelNode<String>>>) -> HashMap<&'p LabelNode<String>, HashSet<&'a LabelNode<String>>> {
+        sim.into_iter().map(|(u, sim_u)| {
+            (u, sim_u.into_iter().flat_map(|class| self.members(class).iter().copied()).collect())
+        }).collect()
+    }
+
+    /// Runs `method` with `pattern` on the compressed graph, and decompresses the result, e.g.
+    /// `compressed.simulate(&pattern, Simulation::get_simulation_inter_dense)`.
+    pub fn simulate<'p>(
+        &'p self,
+        pattern: &'p StandardLabeledGraph,
+        method: impl FnOnce(&'p StandardLabeledGraph, &'p StandardLabeledGraph) -> HashMap<&'p LabelNode<String>, HashSet<&'p LabelNode<String>>>,
+    ) -> HashMap<&'p LabelNode<String>, HashSet<&'a LabelNode<String>>> {
+        self.decompress(method(pattern, &self.graph))
+    }
+}
@@ -0,0 +1,74 @@
+use graph_base::interfaces::graph::Graph;
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::algorithm::simulation::Simulation;
+
+/// The outcome of `get_containment(q1, q2)`.
+pub enum Containment<'a, N> {
+    /// Every match of q2 is a match of q1. `witness` maps every node of q2 to the nodes of q1 it simulates,
+    /// whose matches include its own in every data graph.
+    Contained { witness: HashMap<&'a N, HashSet<&'a N>> },
+    /// A node of q1 that no node of q2 simulates: q2 matches itself, but q1 has no match in it.
+    Unmatched(&'a N),
+    /// A node of q2 that simulates no node of q1: in q2 itself, it is a match of q2 but of no node of q1.
+    Uncovered(&'a N),
+}
+
+impl<'a, N> Containment<'a, N> {
+    pub fn is_contained(&self) -> bool {
+        matches!(self, Containment::Contained { .. })
+    }
+
+    pub fn witness(&self) -> Option<&HashMap<&'a N, HashSet<&'a N>>> {
+        match self {
+            Containment::Contained { witness } => Some(witness),
+            _ => None,
+        }
+    }
+
+    /// The pattern node that makes the containment fail, of q1 when `Unmatched` and of q2 when `Uncovered`.
+    pub fn counterexample(&self) -> Option<&'a N> {
+        match self {
+            Containment::Contained { .. } => None,
+            Containment::Unmatched(u) | Containment::Uncovered(u) => Some(u),
+        }
+    }
+}
+
+pub trait QueryContainment<'a>: Simulation<'a> {
+    /// Whether q2 (`other`) is contained in q1 (`self`): in every data graph that q2 matches, q1 matches as well,
+    /// and every data node that matches a node of q2 matches a node of q1.
+    ///
+    /// It is decided on `get_simulation_inter` of q1 in q2: q2 is contained in q1 iff every node of q1 is simulated
+    /// by some node of q2 and every node of q2 simulates some node of q1, since simulations compose.
+    fn get_containment(&'a self, other: &'a Self) -> Containment<'a, Self::Node>;
+
+    fn contains(&'a self, other: &'a Self) -> bool {
+        self.get_containment(other).is_contained()
+    }
+}
+
+impl<'a, T: Simulation<'a, Node = N>, N: 'a + Eq + Hash> QueryContainment<'a> for T
+where
+    T: Graph<'a, Node = N>,
+{
+    fn get_containment(&'a self, other: &'a Self) -> Containment<'a, N> {
+        let sim = self.get_simulation_inter(other);
+        if let Some(u) = self.nodes().find(|u| sim[u].is_empty()) {
+            return Containment::Unmatched(u);
+        }
+
+        let mut witness: HashMap<&N, HashSet<&N>> = other.nodes().map(|v| (v, HashSet::new())).collect();
+        for (&u, sim_u) in &sim {
+            for &v in sim_u {
+                witness.get_mut(v).unwrap().insert(u);
+            }
+        }
+        match other.nodes().find(|v| witness[v].is_empty()) {
+            Some(v) => Containment::Uncovered(v),
+            None => Containment::Contained { witness },
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use graph_base::interfaces::graph::Graph;
+use graph_base::interfaces::labeled::Labeled;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::algorithm::bounded::Bound;
+
+/// A fixed-size set of dense node indices.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub(crate) fn new(len: usize) -> Self {
+        BitSet { words: vec![0; len.div_ceil(64)] }
+    }
+
+    pub(crate) fn insert(&mut self, i: usize) -> bool {
+        let (word, bit) = (i / 64, 1u64 << (i % 64));
+        let absent = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        absent
+    }
+
+    pub(crate) fn remove(&mut self, i: usize) -> bool {
+        let (word, bit) = (i / 64, 1u64 << (i % 64));
+        let present = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        present
+    }
+
+    pub(crate) fn contains(&self, i: usize) -> bool {
+        self.words[i / 64] & (1u64 << (i % 64)) != 0
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub(crate) fn union_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    pub(crate) fn intersect_with(&mut self, other: &BitSet) {
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word &= other_word;
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+/// Whether the three sets share an element.
+pub(crate) fn intersects3(a: &BitSet, b: &BitSet, c: &BitSet) -> bool {
+    a.words.iter().zip(&b.words).zip(&c.words).any(|((x, y), z)| x & y & z != 0)
+}
+
+/// Compressed sparse rows: the neighbors of node `i` are `targets[offsets[i]..offsets[i + 1]]`,
+/// reached through the edges `edges[offsets[i]..offsets[i + 1]]`.
+pub(crate) struct Csr {
+    offsets: Vec<u32>,
+    targets: Vec<u32>,
+    edges: Vec<u32>,
+}
+
+impl Csr {
+    /// Builds the rows from `(source, edge, target)` triples, keeping their order within each row.
+    fn new(n: usize, triples: &[(u32, u32, u32)]) -> Self {
+        let mut offsets = vec![0u32; n + 1];
+        for &(source, _, _) in triples {
+            offsets[source as usize + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut next: Vec<u32> = offsets[..n].to_vec();
+        let (mut targets, mut edges) = (vec![0u32; triples.len()], vec![0u32; triples.len()]);
+        for &(source, edge, target) in triples {
+            let slot = next[source as usize] as usize;
+            targets[slot] = target;
+            edges[slot] = edge;
+            next[source as usize] += 1;
+        }
+        Csr { offsets, targets, edges }
+    }
+
+    /// The number of rows.
+    pub(crate) fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub(crate) fn neighbors(&self, i: usize) -> &[u32] {
+        &self.targets[self.offsets[i] as usize..self.offsets[i + 1] as usize]
+    }
+
+    pub(crate) fn edges(&self, i: usize) -> &[u32] {
+        &self.edges[self.offsets[i] as usize..self.offsets[i + 1] as usize]
+    }
+
+    pub(crate) fn degree(&self, i: usize) -> usize {
+        (self.offsets[i + 1] - self.offsets[i]) as usize
+    }
+}
+
+/// The neighbors of every row, without the repetitions of parallel edges.
+pub(crate) fn distinct_neighbors(csr: &Csr) -> Vec<Vec<usize>> {
+    (0..csr.len()).map(|i| {
+        let mut neighbors: Vec<usize> = csr.neighbors(i).iter().map(|&j| j as usize).collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }).collect()
+}
+
+/// A graph mapped to dense `u32` indices, in the order of `Graph::nodes` and `Graph::edges`.
+pub(crate) struct DenseGraph<'a, T: Graph<'a>> {
+    pub(crate) nodes: Vec<&'a T::Node>,
+    pub(crate) edges: Vec<&'a T::Edge>,
+    pub(crate) post: Csr,
+    pub(crate) pre: Csr,
+}
+
+impl<'a, T: Graph<'a>> DenseGraph<'a, T> {
+    pub(crate) fn new(graph: &'a T) -> Self {
+        let nodes: Vec<&'a T::Node> = graph.nodes().collect();
+        let index: HashMap<&'a T::Node, u32> = nodes.iter().enumerate().map(|(i, &v)| (v, i as u32)).collect();
+        let mut edges = Vec::new();
+        let mut forward = Vec::new();
+        let mut backward = Vec::new();
+        for (source, edge, target) in graph.get_edges_pair_with_edge() {
+            let (s, t, e) = (index[source], index[target], edges.len() as u32);
+            edges.push(edge);
+            forward.push((s, e, t));
+            backward.push((t, e, s));
+        }
+        let (post, pre) = (Csr::new(nodes.len(), &forward), Csr::new(nodes.len(), &backward));
+        DenseGraph { nodes, edges, post, pre }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// For every node, the ends of the paths from it along `csr` whose length is within `bound`.
+    pub(crate) fn reach(&self, csr: &Csr, bound: Bound) -> Vec<BitSet> {
+        let n = self.len();
+        let (lo, hi) = bound.limits();
+        if lo > 0 {
+            let hi = hi.expect("A bound with a least length has a greatest one");
+            return (0..n).map(|source| walks(csr, n, source, lo, hi)).collect();
+        }
+        let bound = hi.unwrap_or(usize::MAX);
+        let mut depth = vec![usize::MAX; n];
+        let mut queue = VecDeque::new();
+        (0..n).map(|source| {
+            let mut reached = BitSet::new(n);
+            let mut visited = vec![source];
+            depth[source] = 0;
+            queue.push_back(source);
+            while let Some(v) = queue.pop_front() {
+                reached.insert(v);
+                if depth[v] == bound {
+                    continue;
+                }
+                for &w in csr.neighbors(v) {
+                    let w = w as usize;
+                    if depth[w] == usize::MAX {
+                        depth[w] = depth[v] + 1;
+                        visited.push(w);
+                        queue.push_back(w);
+                    }
+                }
+            }
+            for v in visited {
+                depth[v] = usize::MAX;
+            }
+            reached
+        }).collect()
+    }
+}
+
+/// The ends of the paths from `source` along `csr` with `lo` to `hi` edges. The paths may repeat nodes,
+/// so they are followed layer by layer rather than by distance.
+pub(crate) fn walks(csr: &Csr, n: usize, source: usize, lo: usize, hi: usize) -> BitSet {
+    let mut ends = BitSet::new(n);
+    let mut layer = BitSet::new(n);
+    layer.insert(source);
+    if lo == 0 {
+        ends.insert(source);
+    }
+    for length in 1..=hi {
+        let mut next = BitSet::new(n);
+        for v in layer.iter() {
+            for &w in csr.neighbors(v) {
+                next.insert(w as usize);
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        if length >= lo {
+            ends.union_with(&next);
+        }
+        layer = next;
+    }
+    ends
+}
+
+/// Converts the dense relation back to the node references of both graphs.
+pub(crate) fn to_relation<'a, T: Graph<'a>>(
+    pattern: &DenseGraph<'a, T>,
+    data: &DenseGraph<'a, T>,
+    sim: &[BitSet],
+) -> HashMap<&'a T::Node, HashSet<&'a T::Node>> {
+    pattern.nodes.iter().zip(sim).map(|(&u, sim_u)| {
+        (u, sim_u.iter().map(|v| data.nodes[v]).collect())
+    }).collect()
+}
+
+/// sim(u) := {v | label_same(u, v)} for every node u of `pattern`.
+pub(crate) fn label_candidates<'a, T: Labeled<'a>>(graph: &T, pattern: &DenseGraph<'a, T>, data: &DenseGraph<'a, T>) -> Vec<BitSet> {
+    pattern.nodes.iter().map(|&u| {
+        let mut sim_u = BitSet::new(data.len());
+        for (v, &node) in data.nodes.iter().enumerate() {
+            if graph.label_same(u, node) {
+                sim_u.insert(v);
+            }
+        }
+        sim_u
+    }).collect()
+}
+
+/// The counter-based refinement (Henzinger, Henzinger and Kopke) on dense indices.
+///
+/// `count[u * n + w]` is the number of children of `w` in sim(u), and w joins remove(u) once it drops to zero.
+pub(crate) fn refine_counting<'a, T: Graph<'a>>(pattern: &DenseGraph<'a, T>, data: &DenseGraph<'a, T>, sim: &mut [BitSet]) {
+    let n = data.len();
+    let mut count = vec![0u32; pattern.len() * n];
+    let mut remove: Vec<BitSet> = Vec::with_capacity(pattern.len());
+    for (u, sim_u) in sim.iter().enumerate() {
+        for v in sim_u.iter() {
+            for &w in data.pre.neighbors(v) {
+                count[u * n + w as usize] += 1;
+            }
+        }
+        let mut remove_u = BitSet::new(n);
+        for w in (0..n).filter(|&w| data.post.degree(w) != 0 && count[u * n + w] == 0) {
+            remove_u.insert(w);
+        }
+        remove.push(remove_u);
+    }
+
+    let mut worklist: Vec<usize> = (0..pattern.len()).filter(|&u| !remove[u].is_empty()).collect();
+    while let Some(v) = worklist.pop() {
+        let remove_v = std::mem::replace(&mut remove[v], BitSet::new(n));
+        for &u in pattern.pre.neighbors(v) {
+            let u = u as usize;
+            for w in remove_v.iter() {
+                if !sim[u].remove(w) {
+                    continue;
+                }
+                for &w_prime in data.pre.neighbors(w) {
+                    let count_u = &mut count[u * n + w_prime as usize];
+                    *count_u -= 1;
+                    if *count_u == 0 {
+                        if remove[u].is_empty() {
+                            worklist.push(u);
+                        }
+                        remove[u].insert(w_prime as usize);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The round-based refinement of `get_simulation_native`: for every pattern edge `(u, u')`, drop the `v`
+/// in sim(u) without an edge `(v, v')` accepted by `edge_ok` and `v'` in sim(u'), until nothing changes.
+pub(crate) fn refine_rounds<'a, T: Graph<'a>>(
+    pattern: &DenseGraph<'a, T>,
+    data: &DenseGraph<'a, T>,
+    sim: &mut [BitSet],
+    edge_ok: impl Fn(usize, usize) -> bool,
+) {
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for u in 0..pattern.len() {
+            for (&u_prime, &u_edge) in pattern.post.neighbors(u).iter().zip(pattern.post.edges(u)) {
+                let u_prime = u_prime as usize;
+                let sim_u_remove: Vec<usize> = sim[u].iter().filter(|&v| {
+                    !data.post.neighbors(v).iter().zip(data.post.edges(v)).any(|(&v_prime, &v_edge)| {
+                        edge_ok(u_edge as usize, v_edge as usize) && sim[u_prime].contains(v_prime as usize)
+                    })
+                }).collect();
+                for v in sim_u_remove {
+                    sim[u].remove(v);
+                    changed = true;
+                }
+            }
+        }
+    }
+}
+
+/// Refines `sim` so that, for every pattern edge e = (u, u'), each v ∈ sim(u) keeps some v' ∈ dec[e][v] ∩ sim(u'),
+/// where dec[e][v] holds the data nodes that v may reach through e.
+///
+/// `count[e][v]` is |dec[e][v] ∩ sim(u')|, and v leaves sim(u) once it drops to zero.
+pub(crate) fn refine_reach(edges: &[(usize, usize)], dec: &[&[Vec<usize>]], sim: &mut [BitSet]) {
+    let n = dec.first().map_or(0, |dec_e| dec_e.len());
+    let mut incoming: Vec<Vec<usize>> = vec![Vec::new(); sim.len()];
+    let anc: Vec<Vec<Vec<usize>>> = edges.iter().zip(dec).enumerate().map(|(e, (&(_, u_prime), dec_e))| {
+        incoming[u_prime].push(e);
+        let mut anc_e = vec![Vec::new(); n];
+        for (v, dec_v) in dec_e.iter().enumerate() {
+            for &v_prime in dec_v {
+                anc_e[v_prime].push(v);
+            }
+        }
+        anc_e
+    }).collect();
+
+    let mut count: Vec<Vec<usize>> = edges.iter().zip(dec).map(|(&(_, u_prime), dec_e)| {
+        dec_e.iter().map(|dec_v| dec_v.iter().filter(|&&v_prime| sim[u_prime].contains(v_prime)).count()).collect()
+    }).collect();
+    let mut removed: Vec<(usize, usize)> = Vec::new();
+    for (&(u, _), count_e) in edges.iter().zip(&count) {
+        for (v, &count_e_v) in count_e.iter().enumerate() {
+            if count_e_v == 0 && sim[u].remove(v) {
+                removed.push((u, v));
+            }
+        }
+    }
+    while let Some((u_prime, v_prime)) = removed.pop() {
+        for &e in &incoming[u_prime] {
+            let u = edges[e].0;
+            for &v in &anc[e][v_prime] {
+                count[e][v] -= 1;
+                if count[e][v] == 0 && sim[u].remove(v) {
+                    removed.push((u, v));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::algorithm::bounded::Bound;
+
+/// Why a data node `v` left (or never entered) sim(u).
+pub enum Reason<'a, N> {
+    /// `label_same(u, v)` does not hold.
+    LabelMismatch,
+    /// `u` has children but `v` has none.
+    DeadEnd,
+    /// For the pattern edge `(u, child)`, no child of `v` (or, with a `bound`, no end of a path from `v`
+    /// whose length is within `bound`) is left in sim(child). `cause` is the removed pair that took away the last one,
+    /// or `None` when there was none from the start.
+    Unmatched {
+        child: &'a N,
+        bound: Option<Bound>,
+        cause: Option<(&'a N, &'a N)>,
+    },
+}
+
+/// The removal of a pair: its reason, and the round of the main loop and the global step it happened in.
+/// Pairs excluded from the initial candidates have round and step 0.
+pub struct Removal<'a, N> {
+    pub reason: Reason<'a, N>,
+    pub round: usize,
+    pub step: usize,
+}
+
+/// The result of an explaining simulation: the relation, plus the removal of every pair that is not in it.
+pub struct SimulationExplanation<'a, N> {
+    pub(crate) relation: HashMap<&'a N, HashSet<&'a N>>,
+    pub(crate) removals: HashMap<(&'a N, &'a N), Removal<'a, N>>,
+    // The pattern node whose candidates became empty, and the step, when the algorithm gave up early.
+    pub(crate) emptied: Option<(&'a N, usize)>,
+}
+
+impl<'a, N: Eq + Hash> SimulationExplanation<'a, N> {
+    pub(crate) fn new() -> Self {
+        SimulationExplanation { relation: HashMap::new(), removals: HashMap::new(), emptied: None }
+    }
+
+    pub(crate) fn record(&mut self, u: &'a N, v: &'a N, reason: Reason<'a, N>, round: usize, step: usize) {
+        self.removals.insert((u, v), Removal { reason, round, step });
+    }
+
+    pub fn relation(&self) -> &HashMap<&'a N, HashSet<&'a N>> {
+        &self.relation
+    }
+
+    pub fn into_relation(self) -> HashMap<&'a N, HashSet<&'a N>> {
+        self.relation
+    }
+
+    pub fn removal(&self, u: &'a N, v: &'a N) -> Option<&Removal<'a, N>> {
+        self.removals.get(&(u, v))
+    }
+
+    /// The pattern node whose candidates became empty, when the algorithm returned early without a match.
+    pub fn emptied(&self) -> Option<&'a N> {
+        self.emptied.map(|(u, _)| u)
+    }
+}
+
+impl<'a, N: Eq + Hash + Display> SimulationExplanation<'a, N> {
+    /// A derivation of why `v` is not in sim(u), one line per removal, following the causes back to
+    /// the initial label mismatch, dead end or missing candidate.
+    pub fn explain(&self, u: &'a N, v: &'a N) -> String {
+        let mut lines = Vec::new();
+        if let Some((w, step)) = self.emptied {
+            lines.push(format!("there is no match at all: sim({}) became empty at step {}", w, step));
+        }
+        if self.relation.get(u).is_some_and(|sim_u| sim_u.contains(v)) {
+            lines.push(format!("{} simulates {}", v, u));
+            return lines.join("\n");
+        }
+
+        let mut pair = Some((u, v));
+        while let Some((u, v)) = pair.take() {
+            let Some(removal) = self.removals.get(&(u, v)) else {
+                lines.push(format!("{} was still a candidate for {} when the algorithm stopped", v, u));
+                break;
+            };
+            match &removal.reason {
+                Reason::LabelMismatch => lines.push(format!("{} does not match {}: their labels differ", v, u)),
+                Reason::DeadEnd => lines.push(format!("{} does not match {}: {} has children but {} has none", v, u, u, v)),
+                Reason::Unmatched { child, bound, cause } => {
+                    let nodes = match bound {
+                        Some(bound) => format!("no node reachable from {} in {} steps", v, bound),
+                        None => format!("no child of {}", v),
+                    };
+                    let since = match cause {
+                        Some((cause_u, cause_v)) => format!("after {} left sim({})", cause_v, cause_u),
+                        None => "from the start".to_string(),
+                    };
+                    lines.push(format!(
+                        "step {} (round {}): {} was removed from sim({}): for the pattern edge ({}, {}), {} is in sim({}) {}",
+                        removal.step, removal.round, v, u, u, child, nodes, child, since,
+                    ));
+                    pair = *cause;
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,112 @@
+use graph_base::interfaces::labeled::Labeled;
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::algorithm::dense::{distinct_neighbors, label_candidates, DenseGraph};
+use crate::utils::parallel::{par_map, MaybeSync};
+
+/// The weights and the stopping rule of `get_fractional_simulation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FractionalOptions {
+    /// The weight of label_same(u, v).
+    pub label_weight: f64,
+    /// The weight of matching the children of u among the children of v.
+    pub post_weight: f64,
+    /// The weight of matching the parents of u among the parents of v.
+    pub pre_weight: f64,
+    /// The iteration stops once no score changes by more than `epsilon` in a round.
+    pub epsilon: f64,
+    /// The iteration stops after `max_rounds` rounds in any case.
+    pub max_rounds: usize,
+}
+
+impl Default for FractionalOptions {
+    fn default() -> Self {
+        FractionalOptions { label_weight: 0.2, post_weight: 0.4, pre_weight: 0.4, epsilon: 1e-6, max_rounds: 100 }
+    }
+}
+
+pub trait FractionalSimulation<'a> {
+    type Node: 'a;
+
+    /// A score in [0, 1] for every pair (u, v) of a pattern node and a data node, in the manner of FSim:
+    /// starting from label_same(u, v), every round sets
+    ///
+    /// score(u, v) := label_weight · label_same(u, v)
+    ///              + post_weight · avg_{u' ∈ post(u)} max_{v' ∈ post(v)} score(u', v')
+    ///              + pre_weight  · avg_{u' ∈ pre(u)}  max_{v' ∈ pre(v)}  score(u', v'),
+    ///
+    /// with the weights normalized to a sum of 1, an average over no node being 1 and a maximum over no node 0.
+    /// With `pre_weight` 0 the pairs of score 1 are those of `get_simulation_inter`, and otherwise those of
+    /// `get_dual_simulation`, once enough rounds are run. Panics if a weight is negative or all are 0.
+    fn get_fractional_simulation(&'a self, other: &'a Self, options: FractionalOptions) -> HashMap<(&'a Self::Node, &'a Self::Node), f64>;
+}
+
+impl<'a, T> FractionalSimulation<'a> for T
+where
+    T: Labeled<'a> + MaybeSync,
+    T::Node: 'a,
+{
+    type Node = T::Node;
+
+    fn get_fractional_simulation(&'a self, other: &'a Self, options: FractionalOptions) -> HashMap<(&'a Self::Node, &'a Self::Node), f64> {
+        let weights = [options.label_weight, options.post_weight, options.pre_weight];
+        assert!(weights.iter().all(|&w| w >= 0.0) && weights.iter().sum::<f64>() > 0.0, "Invalid fractional simulation weights: {:?}", weights);
+        let total: f64 = weights.iter().sum();
+        let [label_weight, post_weight, pre_weight] = weights.map(|w| w / total);
+
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+        let (n, m) = (pattern.len(), data.len());
+        let (pattern_post, pattern_pre) = (distinct_neighbors(&pattern.post), distinct_neighbors(&pattern.pre));
+        let (data_post, data_pre) = (distinct_neighbors(&data.post), distinct_neighbors(&data.pre));
+
+        // score[u * m + v]
+        let labels = label_candidates(self, &pattern, &data);
+        let label = |u: usize, v: usize| if labels[u].contains(v) { 1.0 } else { 0.0 };
+        let mut score: Vec<f64> = (0..n * m).map(|i| label(i / m, i % m)).collect();
+
+        for _ in 0..options.max_rounds {
+            let ratio = |score: &[f64], a: &[usize], b: &[usize]| {
+                if a.is_empty() {
+                    return 1.0;
+                }
+                let best = |u_prime: usize| b.iter().map(|&v_prime| score[u_prime * m + v_prime]).fold(0.0, f64::max);
+                a.iter().map(|&u_prime| best(u_prime)).sum::<f64>() / a.len() as f64
+            };
+            let rows = par_map((0..n).collect(), |u| {
+                (0..m).map(|v| {
+                    label_weight * label(u, v)
+                        + post_weight * ratio(&score, &pattern_post[u], &data_post[v])
+                        + pre_weight * ratio(&score, &pattern_pre[u], &data_pre[v])
+                }).collect::<Vec<f64>>()
+            });
+            let next: Vec<f64> = rows.into_iter().flatten().collect();
+            let delta = score.iter().zip(&next).map(|(s, t)| (s - t).abs()).fold(0.0, f64::max);
+            score = next;
+            if delta <= options.epsilon {
+                break;
+            }
+        }
+
+        let mut scores = HashMap::new();
+        for (u, &u_node) in pattern.nodes.iter().enumerate() {
+            for (v, &v_node) in data.nodes.iter().enumerate() {
+                scores.insert((u_node, v_node), score[u * m + v]);
+            }
+        }
+        scores
+    }
+}
+
+/// The crisp relation of the scores: v ∈ sim(u) iff score(u, v) ≥ `threshold`. Every pattern node of the scores is a key.
+pub fn threshold<'a, N: Eq + Hash>(scores: &HashMap<(&'a N, &'a N), f64>, threshold: f64) -> HashMap<&'a N, HashSet<&'a N>> {
+    let mut sim: HashMap<&'a N, HashSet<&'a N>> = HashMap::new();
+    for (&(u, v), &score) in scores {
+        let sim_u = sim.entry(u).or_default();
+        if score >= threshold {
+            sim_u.insert(v);
+        }
+    }
+    sim
+}
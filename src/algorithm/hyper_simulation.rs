@@ -6,14 +6,16 @@ use log::{info, warn};
 
 use serde::{Serialize, Deserialize};
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::error::Error;
+use std::path::{Path, PathBuf};
 
 
-use graph_base::interfaces::{edge::{DirectedHyperedge, Hyperedge}, graph::SingleId, hypergraph::{ContainedDirectedHyperedge, ContainedHyperedge, DirectedHypergraph, Hypergraph}, typed::{Type, Typed}};
+use graph_base::interfaces::{edge::{DirectedHyperedge, Hyperedge}, graph::SingleId, hypergraph::{ContainedDirectedHyperedge, ContainedHyperedge, DirectedHypergraph, HyperedgeList, Hypergraph}, typed::Typed};
 
-use crate::{algorithm::simulation, utils::logger::init_global_logger_once};
+use crate::utils::logger::init_global_logger_once;
 use crate::utils::logger::TraceLog;
+use crate::utils::parallel::{par_map, MaybeSync};
 
 pub trait LMatch {
     type Edge;
@@ -48,13 +50,13 @@ impl<'a, E: Hyperedge> SematicCluster<'a, E> {
     }
 }
 
-pub trait Delta<'a> {
+pub trait Delta<'a>: MaybeSync {
     type Node;
     type Edge: Hyperedge;
     fn get_sematic_clusters(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> &'a Vec<(SematicCluster<'a, Self::Edge>, SematicCluster<'a, Self::Edge>)>;
 }
 
-pub trait DMatch<'a> {
+pub trait DMatch<'a>: MaybeSync {
     type Edge: Hyperedge;
     // fn d_match_mut(&mut self, e: &SematicCluster<'a, Self::Edge>, e_prime: &SematicCluster<'a, Self::Edge>) -> &HashSet<(usize, usize)>;
     fn d_match(&self, e: &SematicCluster<'a, Self::Edge>, e_prime: &SematicCluster<'a, Self::Edge>) -> &HashSet<(usize, usize)>;
@@ -67,16 +69,35 @@ pub trait LPredicate<'a>: Hypergraph<'a> {
 }
 
 pub trait HyperSimulation<'a>: Hypergraph<'a> {
-    fn get_simulation_fixpoint(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_simulation_recursive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_soft_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_hyper_simulation_naive(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_hyper_simulation_effect(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_hyper_simulation_effect_pass_by(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, type_same_lookup: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
-    fn get_hyper_simulation_effect_by_id(&'a self, hc_map: &HashMap<(usize, usize), Vec<((usize, usize), HashSet<(usize, usize)>)>>) -> HashSet<(usize, usize)>;
-    fn get_hyper_simulation_strict(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+    fn get_simulation_fixpoint(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+    fn get_simulation_recursive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+    fn get_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+    fn get_soft_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+    /// The hyper simulation, with the trace if `options.trace`.
+    fn get_hyper_simulation_naive(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node>;
+    fn get_hyper_simulation_effect(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node>;
+    fn get_hyper_simulation_effect_pass_by(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, type_same_lookup: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node>;
+    /// The candidates of the trace are the pairs of `hc_map`.
+    fn get_hyper_simulation_effect_by_id(&'a self, hc_map: &HcMap, options: &HyperSimulationOptions) -> TracedIdSimulation;
+    /// `get_hyper_simulation_effect_by_id` on an hc_map streamed as entries, which are never held all at once.
+    fn get_hyper_simulation_effect_by_id_stream(&'a self, hc_map: impl IntoIterator<Item = HcEntry>, options: &HyperSimulationOptions) -> TracedIdSimulation;
+    /// `get_hyper_simulation_effect_by_id` on an hc_map file written by `write_hc_map`, read a chunk at a time.
+    fn get_hyper_simulation_effect_by_id_file(&'a self, path: impl AsRef<Path>, options: &HyperSimulationOptions) -> Result<TracedIdSimulation, Box<dyn Error>>;
+    fn get_hyper_simulation_strict(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node>;
 }
+/// Hyper simulation on directed hyperedges, whose tail (`src`) and head (`dst`) are told apart.
+///
+/// v ∈ sim(u) iff type_same(u, v) and, for every pattern hyperedge e containing u, some data hyperedge e' containing v
+/// with l_predicate_edge(e, e') has v on the side of u, and for every node u' of the tail (head) of e, a node of sim(u')
+/// in the tail (head) of e'.
+pub trait DirectedHyperSimulation<'a>: DirectedHypergraph<'a>
+where Self::Edge: DirectedHyperedge {
+    fn get_directed_hyper_simulation_naive(&'a self, other: &'a Self, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+    /// `get_directed_hyper_simulation_naive` with a worklist: every pair is checked once, and afterwards only the
+    /// pairs whose check read a deleted pair are checked again.
+    fn get_directed_hyper_simulation_effect(&'a self, other: &'a Self, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+}
+
 // struct MultiWriter<W1: Write, W2: Write> {
 //     w1: W1,
 //     w2: W2,
@@ -96,16 +117,111 @@ pub trait HyperSimulation<'a>: Hypergraph<'a> {
 
 
 impl<'a, H> HyperSimulation<'a> for H 
-where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
-    fn get_simulation_fixpoint(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        todo!()
+where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> + MaybeSync, H::Node: MaybeSync {
+    fn get_simulation_fixpoint(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        options.init_logger();
+
+        info!("Start Fixpoint Hyper Simulation");
+
+        let self_contained_hyperedge = self.get_hyperedges_list();
+        let other_contained_hyperedge = other.get_hyperedges_list();
+        let l_match = &*l_match;
+
+        let mut simulation = l_match_initial_simulation(self, other, &self_contained_hyperedge, &other_contained_hyperedge, l_match);
+
+        info!("END Initial, sim: is ");
+        for (u, v_set) in &simulation {
+            info!("\tsim({}) = {:?}", u.id(), v_set.iter().map(|v| v.id()).collect::<Vec<_>>());
+        }
+
+        // watchers[(u, v')] -> { v | the check of (u, v) reads whether v' is in sim(u) }
+        let mut watchers: HashMap<NodePair<'a, Self>, HashSet<&'a Self::Node>> = HashMap::new();
+        for (&u, v_set) in &simulation {
+            for &v in v_set {
+                for e in self.contained_hyperedges(&self_contained_hyperedge, u) {
+                    for e_prime in other.contained_hyperedges(&other_contained_hyperedge, v) {
+                        if !self.l_predicate_edge(e, e_prime) {
+                            continue;
+                        }
+                        for u_prime in l_match.dom(e, e_prime) {
+                            for v_prime in l_match.l_match_with_node(e, e_prime, *u_prime).iter().filter_map(|id| other.get_node_by_id(*id)) {
+                                watchers.entry((u, v_prime)).or_default().insert(v);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Every candidate pair is checked once, afterwards only the pairs watching a deleted pair are re-checked.
+        let mut queue: VecDeque<(&'a Self::Node, &'a Self::Node)> = simulation.iter().flat_map(|(&u, v_set)| v_set.iter().map(move |&v| (u, v))).collect();
+        let mut queued: HashSet<(&'a Self::Node, &'a Self::Node)> = queue.iter().copied().collect();
+
+        while let Some((u, v)) = queue.pop_front() {
+            queued.remove(&(u, v));
+            let sim_u = simulation.get(u).unwrap();
+            if !sim_u.contains(v) {
+                continue;
+            }
+            if l_match_supported(self, other, &self_contained_hyperedge, &other_contained_hyperedge, l_match, u, v, |v_prime| sim_u.contains(v_prime)) {
+                continue;
+            }
+
+            info!("Deleting {} -> {}", u.id(), v.id());
+            let sim_u = simulation.get_mut(u).unwrap();
+            sim_u.remove(v);
+            if let Some(dependents) = watchers.get(&(u, v)) {
+                for &w in dependents {
+                    if sim_u.contains(w) && queued.insert((u, w)) {
+                        queue.push_back((u, w));
+                    }
+                }
+            }
+        }
+
+        simulation
     }
 
-    fn get_simulation_recursive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        todo!()
+    fn get_simulation_recursive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        options.init_logger();
+
+        info!("Start Recursive Hyper Simulation");
+
+        let self_contained_hyperedge = self.get_hyperedges_list();
+        let other_contained_hyperedge = other.get_hyperedges_list();
+        let l_match = &*l_match;
+
+        let candidates = l_match_initial_simulation(self, other, &self_contained_hyperedge, &other_contained_hyperedge, l_match);
+
+        let mut solver = RecursiveSimulation {
+            graph: self,
+            other,
+            self_contained_hyperedge: &self_contained_hyperedge,
+            other_contained_hyperedge: &other_contained_hyperedge,
+            l_match,
+            candidates: &candidates,
+            memo: HashMap::new(),
+            dependents: HashMap::new(),
+        };
+
+        let mut simulation: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = self.nodes().map(|u| (u, HashSet::new())).collect();
+        for u in self.nodes() {
+            for &v in candidates.get(u).unwrap() {
+                solver.decide((u, v), None);
+            }
+        }
+
+        // A pair decided early may be invalidated by a later query, so the result is read off the final memo.
+        for ((u, v), holds) in solver.memo {
+            if holds {
+                simulation.get_mut(u).unwrap().insert(v);
+            }
+        }
+
+        simulation
     }
 
-    fn get_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+    fn get_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
         
         // let log_file = File::create("hyper-simulation.log")
         //     .expect("Failed to create log file");
@@ -118,7 +234,7 @@ where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
         //     .target(env_logger::Target::Pipe(Box::new(multi_writer)))
         //     .init();
 
-        init_global_logger_once("hyper-simulation.log");
+        options.init_logger();
 
         info!("Start Naive Hyper Simulation");
 
@@ -207,8 +323,8 @@ where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
         simulation
     }
 
-    fn get_soft_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        init_global_logger_once("hyper-simulation.log");
+    fn get_soft_simulation_naive(&'a self, other: &'a Self, l_match: &mut impl LMatch<Edge = Self::Edge>, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        options.init_logger();
 
         info!("Start Naive Hyper Simulation");
 
@@ -298,294 +414,411 @@ where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
 
     }
 
-    fn get_hyper_simulation_naive(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        init_global_logger_once("logs/hyper-simulation.log");
-        let mut hs_trace = HyperSimulationTrace::new();
-        let mut simulation: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = self.nodes().map(|u| {
-            let res = other.nodes().filter(|v| {
-                if self.type_same(u, *v) {
-                    let sematic_clusters = delta.get_sematic_clusters(u, v);
-                    for (cluster_u, cluster_v) in sematic_clusters {
-                        let d_match_set = d_match.d_match(cluster_u, cluster_v);
-                        if !d_match_set.contains(&(u.id(), v.id())) {
-                            // Add the trace that nodes (u, v) are deleted by the `sematic_clusters`
-                            hs_trace.add_base_event(cluster_u.id, d_match_set.clone());
-                            return false;
-                        }
-                    }
-                    return true;
-                }
-                false
-            }).collect();
-            (u, res)
-        }).collect();
+    fn get_hyper_simulation_naive(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node> {
+        hyper_simulation_refine(self, other, delta, d_match, options, false)
+    }
 
-        info!("END Initial, raw-sim: is ");
-        for (u, v_set) in &simulation {
-            info!("\tsim({}) = {:?}", u.id(), v_set.iter().map(|v| v.id()).collect::<Vec<_>>());
+    fn get_hyper_simulation_strict(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node> {
+        hyper_simulation_refine(self, other, delta, d_match, options, true)
+    }
+
+    fn get_hyper_simulation_effect(
+        &'a self,
+        other: &'a Self,
+        delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>,
+        d_match: &impl DMatch<'a, Edge = Self::Edge>,
+        options: &HyperSimulationOptions,
+    ) -> TracedSimulation<'a, Self::Node> {
+        options.init_logger();
+        let mut hs_trace = options.records_trace().then(HyperSimulationTrace::new);
+
+        // 每个 u 的候选可以并行计算
+        let us: Vec<&'a Self::Node> = self.nodes().collect();
+        let vs: Vec<&'a Self::Node> = other.nodes().collect();
+        let candidates = par_map(us, |u| {
+            vs.iter().filter(|&&v| self.type_same(u, v)).map(|&v| {
+                ((u.id(), v.id()), hyper_candidate(u, v, delta, d_match))
+            }).collect::<Vec<_>>()
+        });
+        let mut engine = HyperSimulationEngine::new();
+        for (pair, clusters) in candidates.into_iter().flatten() {
+            engine.insert_candidate(pair, clusters, hs_trace.as_mut());
         }
 
-        let mut simulation_by_id: HashSet<(usize, usize)> = simulation.iter().flat_map(|(u, v_set)| {
-            v_set.iter().map(move |v| (u.id(), v.id()))
-        }).collect();
+        let pi = engine.cascade(hs_trace.as_mut());
+        (hyper_simulation_by_node(self, other.nodes(), pi), hs_trace.and_then(|hs_trace| options.finish_trace(hs_trace)))
+    }
 
-        let mut changed = true;
-        while changed {
-            changed = false;
-            for u in self.nodes() {
-                let mut need_delete = Vec::new();
-                for v in simulation.get(u).unwrap() {
-                    info!("Checking {} -> {}", u.id(), v.id());
-                    let mut _delete = false;
+    fn get_hyper_simulation_effect_pass_by(&'a self, _other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, type_same_lookup: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>, options: &HyperSimulationOptions) -> TracedSimulation<'a, Self::Node> {
+        options.init_logger();
+        let mut hs_trace = options.records_trace().then(HyperSimulationTrace::new);
 
-                    let sematic_clusters = delta.get_sematic_clusters(u, v);
-                    for (cluster_u, cluster_v) in sematic_clusters {
-                        let d_relation = d_match.d_match(cluster_u, cluster_v);
-                        // Check if for all (u_id, v_id) in d_relation, (u_id, v_id) is in simulation, i.e., d_relation is a subset of simulation_by_id
-                        if !d_relation.is_subset(&simulation_by_id) {
-                            info!("Deleting {} -> {}", u.id(), v.id());
-                            // Add the trace that nodes (u, v) are deleted by the `sematic_clusters`
-                            let uncoverd: HashSet<(usize, usize)> = d_relation.difference(&simulation_by_id).copied().collect();
-                            hs_trace.add_derivation_event(cluster_u.id, uncoverd);
-                            _delete = true;
-                            break;
-                        }
-                    }
+        let mut engine = HyperSimulationEngine::new();
+        for u in self.nodes() {
+            for &v in type_same_lookup.get(u).into_iter().flatten() {
+                engine.insert_candidate((u.id(), v.id()), hyper_candidate(u, v, delta, d_match), hs_trace.as_mut());
+            }
+        }
 
-                    if _delete {
-                        need_delete.push(v.clone());
-                    }
-                }
+        let pi = engine.cascade(hs_trace.as_mut());
+        let vs = type_same_lookup.values().flatten().copied();
+        (hyper_simulation_by_node(self, vs, pi), hs_trace.and_then(|hs_trace| options.finish_trace(hs_trace)))
+    }
 
-                for v in need_delete {
-                    simulation.get_mut(u).unwrap().remove(v);
-                    simulation_by_id.remove(&(u.id(), v.id()));
-                    changed = true;
-                }
-            }
+    fn get_hyper_simulation_effect_by_id(&'a self, hc_map: &HcMap, options: &HyperSimulationOptions) -> TracedIdSimulation {
+        // ==========================================
+        // hc_map 参数说明：
+        // ==========================================
+        // hc_map 是一个预计算的语义集群和 D-match 关系映射表，用于完全在 ID 空间内执行 Hyper Simulation 计算。
+        // 
+        // 结构：HashMap<(u_id, v_id), Vec<((cu_id, cv_id), D_match_set)>>
+        // 
+        // 键 (u_id, v_id)：
+        //   - 候选节点对，其中 u_id 属于 self，v_id 属于 other
+        //   - 代表可能参加 Hyper Simulation 的节点对
+        // 
+        // 值 Vec<((cu_id, cv_id), D_match_set)>：
+        //   - 该节点对相关的所有语义集群及其 D-match 关系
+        //   - (cu_id, cv_id)：语义集群对的 ID（u 侧和 v 侧）
+        //   - D_match_set：HashSet<(usize, usize)>，该集群对的 D-match 结果
+        //     即满足 d_match(cluster_u, cluster_v) 的所有节点对 (u'_id, v'_id)
+        // 
+        // 调用前的准备步骤（由调用者负责）：
+        //   1. 计算 type_same 的两个图之间所有可兼容的节点对
+        //   2. 对每个节点对，调用 delta.get_sematic_clusters() 获取语义集群
+        //   3. 对每个集群对，调用 d_match.d_match() 计算 D-match 集合
+        //   4. 构建此 hc_map 并传入
+        //
+        // 函数内执行：
+        //   - 完全基于 ID-based 的数据结构，不涉及具体的节点类型
+        //   - 由 HyperSimulationEngine 执行初始化、V_C 构建、级联删除
+        //   - 返回最终的 Hyper Simulation 结果集合
+        
+        options.init_logger();
+
+        let mut engine = HyperSimulationEngine::new();
+        for (&pair, clusters) in hc_map {
+            engine.insert_ref(pair, clusters);
         }
+        engine.run_traced(options)
+    }
 
-        hs_trace.store_trace_file("logs/hyper_simulation.trace").unwrap();
+    fn get_hyper_simulation_effect_by_id_stream(&'a self, hc_map: impl IntoIterator<Item = HcEntry>, options: &HyperSimulationOptions) -> TracedIdSimulation {
+        options.init_logger();
 
-        return simulation;
+        hc_map.into_iter().collect::<HyperSimulationEngine>().run_traced(options)
     }
 
-    fn get_hyper_simulation_strict(&'a self, other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        init_global_logger_once("logs/hyper-simulation.log");
-        let mut hs_trace = HyperSimulationTrace::new();
-        let mut simulation: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = self.nodes().map(|u| {
-            let res = other.nodes().filter(|v| {
-                if self.type_same(u, *v) {
-                    let sematic_clusters = delta.get_sematic_clusters(u, v);
-                    // Highlight!
-                    if sematic_clusters.len() == 0 {
-                        info!("Deleting {} -> {} because no sematic cluster", u.id(), v.id());
-                        return false;
-                    }
-                    info!("Checking {} -> {}, sematic clusters size: {}", u.id(), v.id(), sematic_clusters.len());
-                    for (cluster_u, cluster_v) in sematic_clusters {
-                        let d_match_set = d_match.d_match(cluster_u, cluster_v);
-                        if !d_match_set.contains(&(u.id(), v.id())) {
-                            // Add the trace that nodes (u, v) are deleted by the `sematic_clusters`
-                            hs_trace.add_base_event(cluster_u.id, d_match_set.clone());
-                            return false;
-                        }
-                    }
-                    return true;
-                }
-                false
-            }).collect();
-            (u, res)
-        }).collect();
+    fn get_hyper_simulation_effect_by_id_file(&'a self, path: impl AsRef<Path>, options: &HyperSimulationOptions) -> Result<TracedIdSimulation, Box<dyn Error>> {
+        options.init_logger();
 
-        info!("END Initial, raw-sim: is ");
-        for (u, v_set) in &simulation {
-            info!("\tsim({}) = {:?}", u.id(), v_set.iter().map(|v| v.id()).collect::<Vec<_>>());
-        }
+        let mut engine = HyperSimulationEngine::new();
+        engine.extend_from_file(path)?;
+        Ok(engine.run_traced(options))
+    }
+}
 
-        let mut simulation_by_id: HashSet<(usize, usize)> = simulation.iter().flat_map(|(u, v_set)| {
-            v_set.iter().map(move |v| (u.id(), v.id()))
+/// `get_hyper_simulation_naive`, or `_strict` if `strict`: the strict one also removes the
+/// candidates without any semantic cluster, which the naive one keeps.
+fn hyper_simulation_refine<'a, H>(
+    graph: &'a H,
+    other: &'a H,
+    delta: &'a impl Delta<'a, Node = H::Node, Edge = H::Edge>,
+    d_match: &impl DMatch<'a, Edge = H::Edge>,
+    options: &HyperSimulationOptions,
+    strict: bool,
+) -> TracedSimulation<'a, H::Node>
+where H: Hypergraph<'a> + Typed<'a> {
+    options.init_logger();
+    let mut hs_trace = options.records_trace().then(HyperSimulationTrace::new);
+    let mut simulation: HashMap<&'a H::Node, HashSet<&'a H::Node>> = graph.nodes().map(|u| {
+        let res = other.nodes().filter(|&v| graph.type_same(u, v)).filter(|&v| {
+            if let Some(hs_trace) = hs_trace.as_mut() {
+                hs_trace.add_candidate((u.id(), v.id()));
+            }
+            let sematic_clusters = delta.get_sematic_clusters(u, v);
+            // Highlight!
+            if strict && sematic_clusters.is_empty() {
+                info!("Deleting {} -> {} because no sematic cluster", u.id(), v.id());
+                if let Some(hs_trace) = hs_trace.as_mut() {
+                    hs_trace.add_unclustered_event((u.id(), v.id()));
+                }
+                return false;
+            }
+            info!("Checking {} -> {}, sematic clusters size: {}", u.id(), v.id(), sematic_clusters.len());
+            let mismatch = sematic_clusters.iter().find(|(cluster_u, cluster_v)| {
+                !d_match.d_match(cluster_u, cluster_v).contains(&(u.id(), v.id()))
+            });
+            if let Some((cluster_u, cluster_v)) = mismatch {
+                // Add the trace that nodes (u, v) are deleted by the `sematic_clusters`
+                if let Some(hs_trace) = hs_trace.as_mut() {
+                    hs_trace.add_base_event((u.id(), v.id()), (cluster_u.id, cluster_v.id), d_match.d_match(cluster_u, cluster_v).clone());
+                }
+            }
+            mismatch.is_none()
         }).collect();
+        (u, res)
+    }).collect();
 
-        let mut changed = true;
-        while changed {
-            changed = false;
-            for u in self.nodes() {
-                let mut need_delete = Vec::new();
-                for v in simulation.get(u).unwrap() {
-                    info!("Checking {} -> {}", u.id(), v.id());
-                    let mut _delete = false;
+    info!("END Initial, raw-sim: is ");
+    for (u, v_set) in &simulation {
+        info!("\tsim({}) = {:?}", u.id(), v_set.iter().map(|v| v.id()).collect::<Vec<_>>());
+    }
 
-                    let sematic_clusters = delta.get_sematic_clusters(u, v);
-                    for (cluster_u, cluster_v) in sematic_clusters {
+    let mut simulation_by_id: HashSet<(usize, usize)> = simulation.iter().flat_map(|(u, v_set)| {
+        v_set.iter().map(move |v| (u.id(), v.id()))
+    }).collect();
+
+    let mut changed = true;
+    let mut round = 0;
+    while changed {
+        changed = false;
+        round += 1;
+        for u in graph.nodes() {
+            let mut need_delete = Vec::new();
+            for v in simulation.get(u).unwrap() {
+                info!("Checking {} -> {}", u.id(), v.id());
+
+                let sematic_clusters = delta.get_sematic_clusters(u, v);
+                // Check if for all (u_id, v_id) in d_relation, (u_id, v_id) is in simulation, i.e., d_relation is a subset of simulation_by_id
+                let uncovered_cluster = sematic_clusters.iter().find(|(cluster_u, cluster_v)| {
+                    !d_match.d_match(cluster_u, cluster_v).is_subset(&simulation_by_id)
+                });
+                if let Some((cluster_u, cluster_v)) = uncovered_cluster {
+                    info!("Deleting {} -> {}", u.id(), v.id());
+                    // Add the trace that nodes (u, v) are deleted by the `sematic_clusters`
+                    if let Some(hs_trace) = hs_trace.as_mut() {
                         let d_relation = d_match.d_match(cluster_u, cluster_v);
-                        // Check if for all (u_id, v_id) in d_relation, (u_id, v_id) is in simulation, i.e., d_relation is a subset of simulation_by_id
-                        if !d_relation.is_subset(&simulation_by_id) {
-                            info!("Deleting {} -> {}", u.id(), v.id());
-                            // Add the trace that nodes (u, v) are deleted by the `sematic_clusters`
-                            let uncoverd: HashSet<(usize, usize)> = d_relation.difference(&simulation_by_id).copied().collect();
-                            hs_trace.add_derivation_event(cluster_u.id, uncoverd);
-                            _delete = true;
-                            break;
-                        }
-                    }
-
-                    if _delete {
-                        need_delete.push(v.clone());
+                        let uncoverd: HashSet<(usize, usize)> = d_relation.difference(&simulation_by_id).copied().collect();
+                        hs_trace.add_derivation_event((u.id(), v.id()), round, (cluster_u.id, cluster_v.id), uncoverd);
                     }
+                    need_delete.push(*v);
                 }
+            }
 
-                for v in need_delete {
-                    simulation.get_mut(u).unwrap().remove(v);
-                    simulation_by_id.remove(&(u.id(), v.id()));
-                    changed = true;
-                }
+            for v in need_delete {
+                simulation.get_mut(u).unwrap().remove(v);
+                simulation_by_id.remove(&(u.id(), v.id()));
+                changed = true;
             }
         }
+    }
 
-        hs_trace.store_trace_file("logs/hyper_simulation.trace").unwrap();
+    (simulation, hs_trace.and_then(|hs_trace| options.finish_trace(hs_trace)))
+}
 
-        return simulation;
-    }
+/// A cluster pair (Cu, Cv) by id, with its D-match not containing the candidate it belongs to.
+type ClusterMismatch<'d> = ((usize, usize), &'d HashSet<(usize, usize)>);
+
+/// The clusters of a candidate pair (u, v) with their D-match, or the first one whose D-match does not contain
+/// (u, v) (条件 2.a).
+fn hyper_candidate<'a, 'd, N: SingleId + 'a, E: Hyperedge + 'a>(
+    u: &'a N,
+    v: &'a N,
+    delta: &'a impl Delta<'a, Node = N, Edge = E>,
+    d_match: &'d impl DMatch<'a, Edge = E>,
+) -> Result<Vec<ClusterDMatch>, ClusterMismatch<'d>> {
+    delta.get_sematic_clusters(u, v).iter().map(|(cluster_u, cluster_v)| {
+        let d_match_set = d_match.d_match(cluster_u, cluster_v);
+        let c_pair = (cluster_u.id, cluster_v.id);
+        if d_match_set.contains(&(u.id(), v.id())) {
+            Ok((c_pair, d_match_set.clone()))
+        } else {
+            Err((c_pair, d_match_set))
+        }
+    }).collect()
+}
 
-    fn get_hyper_simulation_effect(
-        &'a self,
-        other: &'a Self,
-        delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>,
-        d_match: &impl DMatch<'a, Edge = Self::Edge>,
-    ) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+/// The id pairs of a hyper simulation back as nodes, from `graph` to the nodes of `vs`.
+fn hyper_simulation_by_node<'a, H: Hypergraph<'a>>(
+    graph: &'a H,
+    vs: impl Iterator<Item = &'a H::Node>,
+    pi: HashSet<(usize, usize)>,
+) -> HashMap<&'a H::Node, HashSet<&'a H::Node>> {
+    let id_to_u: HashMap<usize, &'a H::Node> = graph.nodes().map(|u| (u.id(), u)).collect();
+    let id_to_v: HashMap<usize, &'a H::Node> = vs.map(|v| (v.id(), v)).collect();
+    let mut result: HashMap<&'a H::Node, HashSet<&'a H::Node>> = graph.nodes().map(|u| (u, HashSet::new())).collect();
+    for (u_id, v_id) in pi {
+        result.get_mut(id_to_u[&u_id]).unwrap().insert(id_to_v[&v_id]);
+    }
+    result
+}
 
-        init_global_logger_once("logs/hyper-simulation.log");
+/// A hyper simulation with its trace, if one is asked for.
+pub type TracedSimulation<'a, N> = (HashMap<&'a N, HashSet<&'a N>>, Option<HyperSimulationTrace>);
+/// A hyper simulation by id with its trace, if one is asked for.
+pub type TracedIdSimulation = (HashSet<(usize, usize)>, Option<HyperSimulationTrace>);
+/// A cluster pair (Cu, Cv) by id, with its D-match.
+pub type ClusterDMatch = ((usize, usize), HashSet<(usize, usize)>);
+/// An entry of an hc_map: a candidate pair (u_id, v_id) with its cluster pairs.
+pub type HcEntry = ((usize, usize), Vec<ClusterDMatch>);
+/// An hc_map: the cluster pairs of every candidate pair (u_id, v_id).
+pub type HcMap = HashMap<(usize, usize), Vec<ClusterDMatch>>;
+
+/// The worklist cascade of the `effect` hyper simulations, on the ids of the nodes and the semantic clusters.
+///
+/// It is fed a stream of candidate pairs (u, v), each with its cluster pairs (Cu, Cv) and their D-match, and keeps
+/// (u, v) iff the D-match of every cluster pair of (u, v) is kept as well. The candidates are expected to be in the
+/// D-match of their own cluster pairs (条件 2.a), which the engine does not check. The D-match of a cluster pair is
+/// stored once, the first time the pair is seen, so the clusters shared by many candidates are not copied.
+///
+/// ```ignore
+/// let mut engine = HyperSimulationEngine::new();
+/// engine.insert((u_id, v_id), vec![((cu_id, cv_id), d_match_set)]);
+/// let pi: HashSet<(usize, usize)> = engine.run();
+/// ```
+#[derive(Default)]
+pub struct HyperSimulationEngine {
+    // Pi: 当前满足 Hyper Simulation 条件的 (u.id(), v.id()) 集合
+    pi: HashSet<(usize, usize)>,
+    // A_cluster 对应的 D-match，每个簇对只保存一份
+    a_cluster_d_match: HashMap<(usize, usize), HashSet<(usize, usize)>>,
+    // 依赖索引:
+    // D_cluster[(Cu, Cv)] -> { (u, v) \in Pi }
+    d_cluster: HashMap<(usize, usize), HashSet<(usize, usize)>>,
+    // D_pair[(u', v')] -> { (Cu, Cv) \in A_cluster }
+    d_pair: HashMap<(usize, usize), HashSet<(usize, usize)>>,
+}
 
-        // 建立 ID 到节点的映射，方便最后构造返回结果
-        let mut id_to_u: HashMap<usize, &'a Self::Node> = HashMap::new();
-        let mut id_to_v: HashMap<usize, &'a Self::Node> = HashMap::new();
+impl HyperSimulationEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        // 存储节点对与其对应的所有的 Semantic Clusters (HC) 及 D-match 结果
-        let mut hc_map: HashMap<(usize, usize), Vec<((usize, usize), HashSet<(usize, usize)>)>> = HashMap::new();
-        
-        // Pi: 当前满足 Hyper Simulation 条件的 (u.id(), v.id()) 集合
-        let mut pi: HashSet<(usize, usize)> = HashSet::new();
+    /// Adds the candidate `pair` with its cluster pairs and their D-match. The cluster pairs of a pair inserted twice
+    /// are merged.
+    pub fn insert(&mut self, pair: (usize, usize), clusters: Vec<ClusterDMatch>) {
+        for (c_pair, d_match_set) in clusters {
+            self.insert_cluster(pair, c_pair, || d_match_set);
+        }
+        self.pi.insert(pair);
+    }
 
-        // ==========================================
-        // Phase 1: Declarative Initialization
-        // ==========================================
-        
-        // 1. 初始化 Pi 并获取 HC 和 D-match
-        for u in self.nodes() {
-            id_to_u.insert(u.id(), u);
-            for v in other.nodes() {
-                id_to_v.insert(v.id(), v);
-
-                if self.type_same(u, v) {
-                    let sematic_clusters = delta.get_sematic_clusters(u, v);
-                    let mut valid = true;
-                    let mut local_clusters = Vec::new();
-
-                    for (cluster_u, cluster_v) in sematic_clusters {
-                        let cu_id = cluster_u.id;
-                        let cv_id = cluster_v.id;
-                        let d_match_set = d_match.d_match(cluster_u, cluster_v);
-
-                        // 条件 2.a: (u, v) 必须在 D-match 中
-                        if !d_match_set.contains(&(u.id(), v.id())) {
-                            valid = false;
-                            break; // 只要有一个 cluster 失败，(u,v) 就不可能在 Pi 中
-                        }
-                        local_clusters.push(((cu_id, cv_id), d_match_set.clone()));
-                    }
+    /// `insert` without taking the D-match sets, which are only copied for the cluster pairs not seen yet.
+    pub fn insert_ref(&mut self, pair: (usize, usize), clusters: &[ClusterDMatch]) {
+        for (c_pair, d_match_set) in clusters {
+            self.insert_cluster(pair, *c_pair, || d_match_set.clone());
+        }
+        self.pi.insert(pair);
+    }
 
-                    if valid {
-                        pi.insert((u.id(), v.id()));
-                        hc_map.insert((u.id(), v.id()), local_clusters);
-                    }
+    /// `insert` for a pair checked by `hyper_candidate`: one failing 条件 2.a is only added to the trace, with its
+    /// `Base` event.
+    fn insert_candidate(&mut self, pair: (usize, usize), clusters: Result<Vec<ClusterDMatch>, ClusterMismatch>, hs_trace: Option<&mut HyperSimulationTrace>) {
+        match clusters {
+            Ok(clusters) => self.insert(pair, clusters),
+            Err((c_pair, d_match_set)) => {
+                if let Some(hs_trace) = hs_trace {
+                    hs_trace.add_candidate(pair);
+                    hs_trace.add_base_event(pair, c_pair, d_match_set.clone());
                 }
             }
         }
+    }
 
-        info!("完成了 Pi 的初始化和 HC、D-match 的获取，Pi 大小: {}", pi.len());
+    fn insert_cluster(&mut self, pair: (usize, usize), c_pair: (usize, usize), d_match_set: impl FnOnce() -> HashSet<(usize, usize)>) {
+        // 填充 D_cluster
+        self.d_cluster.entry(c_pair).or_default().insert(pair);
 
-        // A_cluster 对应的 D-match 缓存，避免重复计算
-        let mut a_cluster_d_match: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-        
-        // 依赖索引构建:
-        // D_cluster[(Cu, Cv)] -> { (u, v) \in Pi } 
-        let mut d_cluster: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-        // D_pair[(u', v')] -> { (Cu, Cv) \in A_cluster }
-        let mut d_pair: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-
-        for (&(u_id, v_id), clusters) in &hc_map {
-            for ((cu_id, cv_id), d_match_set) in clusters {
-                let c_pair = (*cu_id, *cv_id);
-                
-                // 填充 D_cluster
-                d_cluster.entry(c_pair).or_default().insert((u_id, v_id));
-
-                // 如果这是第一次遇到这个簇对，填充 D_pair
-                if !a_cluster_d_match.contains_key(&c_pair) {
-                    a_cluster_d_match.insert(c_pair, d_match_set.clone());
-
-                    for &(up_id, vp_id) in d_match_set {
-                        d_pair.entry((up_id, vp_id)).or_default().insert(c_pair);
-                    }
-                }
+        // 如果这是第一次遇到这个簇对，填充 D_pair
+        if let std::collections::hash_map::Entry::Vacant(entry) = self.a_cluster_d_match.entry(c_pair) {
+            let d_match_set = entry.insert(d_match_set());
+            for &(up_id, vp_id) in d_match_set.iter() {
+                self.d_pair.entry((up_id, vp_id)).or_default().insert(c_pair);
             }
         }
+    }
 
-        info!("1. 初始化 Pi 并获取 HC 和 D-match");
+    /// Inserts the entries of an hc_map file written by `write_hc_map`, a chunk at a time. The D-match sets of the
+    /// cluster pairs already seen are dropped as they are read.
+    pub fn extend_from_file(&mut self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        for chunk in read_hc_map(path)? {
+            self.extend(chunk?);
+        }
+        Ok(())
+    }
 
-        // 2. 初始化 V_C (Valid Clusters)
-        let mut v_c: HashSet<(usize, usize)> = HashSet::new();
-        for (c_pair, d_match_set) in &a_cluster_d_match {
-            // 条件 2.b: D-match 的所有元素都必须在当前的 Pi 中
-            if d_match_set.is_subset(&pi) {
-                v_c.insert(*c_pair);
+    /// The number of candidate pairs.
+    pub fn len(&self) -> usize {
+        self.pi.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pi.is_empty()
+    }
+
+    /// Runs the cascade and returns the pairs (u_id, v_id) of the hyper simulation.
+    pub fn run(self) -> HashSet<(usize, usize)> {
+        self.cascade(None)
+    }
+
+    /// `run`, with the trace if `options.trace`. The candidates of the trace are the pairs inserted, and its events are
+    /// the removals of the cascade.
+    fn run_traced(self, options: &HyperSimulationOptions) -> TracedIdSimulation {
+        let mut hs_trace = options.records_trace().then(HyperSimulationTrace::new);
+        let pi = self.cascade(hs_trace.as_mut());
+        (pi, hs_trace.and_then(|hs_trace| options.finish_trace(hs_trace)))
+    }
+
+    /// `run`, adding the candidate pairs to `hs_trace` and a `Derivation` for every pair the cascade removes. The
+    /// pairs removed because of the initial V_C are in round 1, and the ones they cascade to in the following rounds.
+    fn cascade(self, mut hs_trace: Option<&mut HyperSimulationTrace>) -> HashSet<(usize, usize)> {
+        let HyperSimulationEngine { mut pi, a_cluster_d_match, d_cluster, d_pair } = self;
+        if let Some(hs_trace) = hs_trace.as_deref_mut() {
+            for &pair in &pi {
+                hs_trace.add_candidate(pair);
             }
         }
+        // The pairs of the D-match of `c_pair` that were out of Pi before `removed`.
+        let uncovered = |c_pair: &(usize, usize), removed: &(usize, usize), pi: &HashSet<(usize, usize)>| -> HashSet<(usize, usize)> {
+            a_cluster_d_match[c_pair].iter().filter(|&pair| pair != removed && !pi.contains(pair)).copied().collect()
+        };
 
-        info!("2. 初始化 V_C (Valid Clusters)");
+        info!("完成了 Pi 的初始化和 HC、D-match 的获取，Pi 大小: {}", pi.len());
+
+        // 1. 初始化 V_C (Valid Clusters)
+        let mut v_c: HashSet<(usize, usize)> = par_map(a_cluster_d_match.iter().collect(), |(&c_pair, d_match_set)| {
+            // 条件 2.b: D-match 的所有元素都必须在当前的 Pi 中
+            d_match_set.is_subset(&pi).then_some(c_pair)
+        }).into_iter().flatten().collect();
 
-        // 3. 找出失效的 (u, v) 加入队列 Q
-        let mut q: VecDeque<(usize, usize)> = VecDeque::new();
-        let mut pi_retained = pi.clone();
+        info!("1. 初始化 V_C (Valid Clusters)");
 
-        for &(u_id, v_id) in &pi {
-            let mut all_in_vc = true;
-            if let Some(clusters) = hc_map.get(&(u_id, v_id)) {
-                for (c_pair, _) in clusters {
-                    if !v_c.contains(c_pair) {
-                        all_in_vc = false;
-                        break;
+        // 2. 找出失效的 (u, v) 加入队列 Q：依赖于不在 V_C 中的簇对的 (u, v)
+        let mut q: VecDeque<((usize, usize), usize)> = VecDeque::new();
+        for (c_pair, dependent_node_pairs) in &d_cluster {
+            if v_c.contains(c_pair) {
+                continue;
+            }
+            for node_pair in dependent_node_pairs {
+                if pi.remove(node_pair) { // Pi = Pi \ Q
+                    if let Some(hs_trace) = hs_trace.as_deref_mut() {
+                        hs_trace.add_derivation_event(*node_pair, 1, *c_pair, uncovered(c_pair, node_pair, &pi));
                     }
+                    q.push_back((*node_pair, 1)); // 加入 Worklist
                 }
             }
-
-            if !all_in_vc {
-                q.push_back((u_id, v_id));      // 加入 Worklist
-                pi_retained.remove(&(u_id, v_id)); // Pi = Pi \ Q
-            }
         }
-        pi = pi_retained;
 
-        info!("3. 找出失效的 (u, v) 加入队列 Q");
+        info!("2. 找出失效的 (u, v) 加入队列 Q");
 
         // ==========================================
-        // Phase 2: Cascade deletions via the queue
+        // Cascade deletions via the queue
         // ==========================================
-        while let Some((up_id, vp_id)) = q.pop_front() {
+        while let Some(((up_id, vp_id), round)) = q.pop_front() {
             // 获取所有依赖于已删除节点对 (u', v') 的簇对 (Cu, Cv)
             if let Some(dependent_clusters) = d_pair.get(&(up_id, vp_id)) {
                 for c_pair in dependent_clusters {
                     // 如果簇对仍然被认为是有效的，现在它失效了
-                    if v_c.contains(c_pair) {
-                        v_c.remove(c_pair); // V_c = V_c \ {(Cu, Cv)}
-
+                    if v_c.remove(c_pair) { // V_c = V_c \ {(Cu, Cv)}
                         // 级联使依赖这个失效簇对的 (u, v) 失效
                         if let Some(dependent_node_pairs) = d_cluster.get(c_pair) {
                             for node_pair in dependent_node_pairs {
-                                if pi.contains(node_pair) {
-                                    pi.remove(node_pair);
-                                    q.push_back(*node_pair);
+                                if pi.remove(node_pair) {
+                                    if let Some(hs_trace) = hs_trace.as_deref_mut() {
+                                        hs_trace.add_derivation_event(*node_pair, round + 1, *c_pair, uncovered(c_pair, node_pair, &pi));
+                                    }
+                                    q.push_back((*node_pair, round + 1));
                                 }
                             }
                         }
@@ -596,350 +829,569 @@ where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
 
         info!("结束了主调用");
 
-        // ==========================================
-        // Phase 3: Construct Output
-        // ==========================================
-        // 将基于 ID 的关系还原为引用的 HashMap
-        let mut result: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = 
-            self.nodes().map(|u| (u, HashSet::new())).collect();
+        pi
+    }
+}
 
-        for (u_id, v_id) in pi {
-            // 由于我们事先在字典中存过映射，这里可以安全取值
-            let u_node = id_to_u[&u_id];
-            let v_node = id_to_v[&v_id];
-            if let Some(set) = result.get_mut(u_node) {
-                set.insert(v_node);
-            }
+impl Extend<HcEntry> for HyperSimulationEngine {
+    fn extend<T: IntoIterator<Item = HcEntry>>(&mut self, iter: T) {
+        for (pair, clusters) in iter {
+            self.insert(pair, clusters);
         }
+    }
+}
 
-        result
+impl FromIterator<HcEntry> for HyperSimulationEngine {
+    fn from_iter<T: IntoIterator<Item = HcEntry>>(iter: T) -> Self {
+        let mut engine = HyperSimulationEngine::new();
+        engine.extend(iter);
+        engine
     }
+}
 
-    fn get_hyper_simulation_effect_pass_by(&'a self, _other: &'a Self, delta: &'a impl Delta<'a, Node = Self::Node, Edge = Self::Edge>, d_match: & impl DMatch<'a, Edge = Self::Edge>, type_same_lookup: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        init_global_logger_once("logs/hyper-simulation.log");
+/// Writes the entries of an hc_map to `path` with bincode, as a sequence of chunks of `chunk_size` entries, so that
+/// it can be read back without holding it all in memory.
+pub fn write_hc_map(path: impl AsRef<Path>, hc_map: impl IntoIterator<Item = HcEntry>, chunk_size: usize) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut chunk: Vec<HcEntry> = Vec::with_capacity(chunk_size.max(1));
+    for entry in hc_map {
+        chunk.push(entry);
+        if chunk.len() >= chunk_size.max(1) {
+            bincode::serialize_into(&mut writer, &chunk)?;
+            chunk.clear();
+        }
+    }
+    if !chunk.is_empty() {
+        bincode::serialize_into(&mut writer, &chunk)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
 
-        // 建立 ID 到节点的映射，方便最后构造返回结果
-        let mut id_to_u: HashMap<usize, &'a Self::Node> = HashMap::new();
-        let mut id_to_v: HashMap<usize, &'a Self::Node> = HashMap::new();
+/// The chunks of an hc_map file written by `write_hc_map`.
+pub fn read_hc_map(path: impl AsRef<Path>) -> Result<HcMapChunks, Box<dyn Error>> {
+    Ok(HcMapChunks { reader: BufReader::new(File::open(path)?) })
+}
 
-        // 存储节点对与其对应的所有的 Semantic Clusters (HC) 及 D-match 结果
-        let mut hc_map: HashMap<(usize, usize), Vec<((usize, usize), HashSet<(usize, usize)>)>> = HashMap::new();
-        
-        // Pi: 当前满足 Hyper Simulation 条件的 (u.id(), v.id()) 集合
-        let mut pi: HashSet<(usize, usize)> = HashSet::new();
+/// An iterator over the chunks of an hc_map file, each decoded when it is reached.
+pub struct HcMapChunks {
+    reader: BufReader<File>,
+}
 
-        // ==========================================
-        // Phase 1: Declarative Initialization
-        // ==========================================
-        
-        // 1. 初始化 Pi 并获取 HC 和 D-match
-        for u in self.nodes() {
-            id_to_u.insert(u.id(), u);
+impl Iterator for HcMapChunks {
+    type Item = Result<Vec<HcEntry>, Box<dyn Error>>;
 
-            if let Some(type_same_vs) = type_same_lookup.get(u) {
-                for v in type_same_vs {
-                    id_to_v.insert(v.id(), v);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.fill_buf() {
+            Ok([]) => None,
+            Ok(_) => Some(bincode::deserialize_from(&mut self.reader).map_err(Into::into)),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
 
-                    let sematic_clusters = delta.get_sematic_clusters(u, v);
-                    let mut valid = true;
-                    let mut local_clusters = Vec::new();
+impl<'a, H> DirectedHyperSimulation<'a> for H
+where H: DirectedHypergraph<'a> + ContainedDirectedHyperedge<'a> + Typed<'a> + LPredicate<'a>, H::Edge: DirectedHyperedge {
+    fn get_directed_hyper_simulation_naive(&'a self, other: &'a Self, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        options.init_logger();
 
-                    for (cluster_u, cluster_v) in sematic_clusters {
-                        let cu_id = cluster_u.id;
-                        let cv_id = cluster_v.id;
-                        let d_match_set = d_match.d_match(cluster_u, cluster_v);
+        info!("Start Naive Directed Hyper Simulation");
 
-                        // 条件 2.a: (u, v) 必须在 D-match 中
-                        if !d_match_set.contains(&(u.id(), v.id())) {
-                            valid = false;
-                            break; // 只要有一个 cluster 失败，(u,v) 就不可能在 Pi 中
-                        }
-                        local_clusters.push(((cu_id, cv_id), d_match_set.clone()));
-                    }
+        let (pattern, data) = (DirectedIncidence::new(self), DirectedIncidence::new(other));
+        let mut simulation: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = self.nodes().map(|u| {
+            let res = other.nodes().filter(|&v| {
+                self.type_same(u, v) && directed_edge_candidates(self, other, &pattern, &data, u, v).iter().all(|(_, e_primes)| !e_primes.is_empty())
+            }).collect();
+            (u, res)
+        }).collect();
 
-                    if valid {
-                        pi.insert((u.id(), v.id()));
-                        hc_map.insert((u.id(), v.id()), local_clusters);
-                    }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for u in self.nodes() {
+                let need_delete: Vec<&'a Self::Node> = simulation[u].iter().copied().filter(|&v| {
+                    let candidates = directed_edge_candidates(self, other, &pattern, &data, u, v);
+                    !directed_supported(&pattern, &data, &candidates, |u_prime, v_prime| simulation[u_prime].contains(v_prime))
+                }).collect();
+                for v in need_delete {
+                    info!("Deleting {} -> {}", u.id(), v.id());
+                    simulation.get_mut(u).unwrap().remove(v);
+                    changed = true;
                 }
             }
         }
 
-        info!("完成了 Pi 的初始化和 HC、D-match 的获取，Pi 大小: {}", pi.len());
+        simulation
+    }
 
-        // A_cluster 对应的 D-match 缓存，避免重复计算
-        let mut a_cluster_d_match: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-        
-        // 依赖索引构建:
-        // D_cluster[(Cu, Cv)] -> { (u, v) \in Pi } 
-        let mut d_cluster: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-        // D_pair[(u', v')] -> { (Cu, Cv) \in A_cluster }
-        let mut d_pair: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-
-        for (&(u_id, v_id), clusters) in &hc_map {
-            for ((cu_id, cv_id), d_match_set) in clusters {
-                let c_pair = (*cu_id, *cv_id);
-                
-                // 填充 D_cluster
-                d_cluster.entry(c_pair).or_default().insert((u_id, v_id));
-
-                // 如果这是第一次遇到这个簇对，填充 D_pair
-                if !a_cluster_d_match.contains_key(&c_pair) {
-                    a_cluster_d_match.insert(c_pair, d_match_set.clone());
-
-                    for &(up_id, vp_id) in d_match_set {
-                        d_pair.entry((up_id, vp_id)).or_default().insert(c_pair);
+    fn get_directed_hyper_simulation_effect(&'a self, other: &'a Self, options: &HyperSimulationOptions) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        options.init_logger();
+
+        info!("Start Effect Directed Hyper Simulation");
+
+        let (pattern, data) = (DirectedIncidence::new(self), DirectedIncidence::new(other));
+        let mut simulation: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = self.nodes().map(|u| (u, HashSet::new())).collect();
+        let mut candidates: HashMap<NodePair<'a, Self>, EdgeCandidates<'a, Self>> = HashMap::new();
+        for u in self.nodes() {
+            for v in other.nodes().filter(|&v| self.type_same(u, v)) {
+                let candidates_uv = directed_edge_candidates(self, other, &pattern, &data, u, v);
+                if candidates_uv.iter().all(|(_, e_primes)| !e_primes.is_empty()) {
+                    simulation.get_mut(u).unwrap().insert(v);
+                    candidates.insert((u, v), candidates_uv);
+                }
+            }
+        }
+
+        // watchers[(u', v')] -> { (u, v) | the check of (u, v) reads whether v' is in sim(u') }
+        let mut watchers: HashMap<NodePair<'a, Self>, HashSet<NodePair<'a, Self>>> = HashMap::new();
+        for (&(u, v), candidates_uv) in &candidates {
+            for (e, e_primes) in candidates_uv {
+                let (src, dst) = &pattern.sides[*e];
+                for e_prime in e_primes {
+                    let (src_prime, dst_prime) = &data.sides[*e_prime];
+                    for (side, side_prime) in [(src, src_prime), (dst, dst_prime)] {
+                        for &u_prime in side {
+                            for &v_prime in side_prime {
+                                watchers.entry((u_prime, v_prime)).or_default().insert((u, v));
+                            }
+                        }
                     }
                 }
             }
         }
 
-        info!("1. 初始化 Pi 并获取 HC 和 D-match");
+        let mut queue: VecDeque<NodePair<'a, Self>> = self.nodes().flat_map(|u| simulation[u].iter().map(move |&v| (u, v))).collect();
+        let mut queued: HashSet<NodePair<'a, Self>> = queue.iter().copied().collect();
+        while let Some((u, v)) = queue.pop_front() {
+            queued.remove(&(u, v));
+            if !simulation[u].contains(v) {
+                continue;
+            }
+            if directed_supported(&pattern, &data, &candidates[&(u, v)], |u_prime, v_prime| simulation[u_prime].contains(v_prime)) {
+                continue;
+            }
 
-        // 2. 初始化 V_C (Valid Clusters)
-        let mut v_c: HashSet<(usize, usize)> = HashSet::new();
-        for (c_pair, d_match_set) in &a_cluster_d_match {
-            // 条件 2.b: D-match 的所有元素都必须在当前的 Pi 中
-            if d_match_set.is_subset(&pi) {
-                v_c.insert(*c_pair);
+            info!("Deleting {} -> {}", u.id(), v.id());
+            simulation.get_mut(u).unwrap().remove(v);
+            for &(w, w_prime) in watchers.get(&(u, v)).into_iter().flatten() {
+                if simulation[w].contains(w_prime) && queued.insert((w, w_prime)) {
+                    queue.push_back((w, w_prime));
+                }
             }
         }
 
-        info!("2. 初始化 V_C (Valid Clusters)");
-
-        // 3. 找出失效的 (u, v) 加入队列 Q
-        let mut q: VecDeque<(usize, usize)> = VecDeque::new();
-        let mut pi_retained = pi.clone();
+        simulation
+    }
+}
 
-        for &(u_id, v_id) in &pi {
-            let mut all_in_vc = true;
-            if let Some(clusters) = hc_map.get(&(u_id, v_id)) {
-                for (c_pair, _) in clusters {
-                    if !v_c.contains(c_pair) {
-                        all_in_vc = false;
-                        break;
+/// The initial candidates shared by the `LMatch` based simulations: `v` is a candidate of `u` if
+/// they have the same type and, for every hyperedge `e` containing `u`, some `e'` containing `v`
+/// with `l_predicate_edge(e, e')` maps `u` onto `v`.
+fn l_match_initial_simulation<'a, H>(
+    graph: &'a H,
+    other: &'a H,
+    self_contained_hyperedge: &HyperedgeList<'a, H>,
+    other_contained_hyperedge: &HyperedgeList<'a, H>,
+    l_match: &impl LMatch<Edge = H::Edge>,
+) -> HashMap<&'a H::Node, HashSet<&'a H::Node>>
+where H: Hypergraph<'a> + Typed<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
+    graph.nodes().map(|u| {
+        let res = other.nodes().filter(|v| {
+            if !graph.type_same(u, v) {
+                return false;
+            }
+            let mut l_match_intersection: Option<HashSet<usize>> = None;
+            for e in graph.contained_hyperedges(self_contained_hyperedge, u) {
+                let mut l_match_union: HashSet<usize> = HashSet::new();
+                for e_prime in other.contained_hyperedges(other_contained_hyperedge, v) {
+                    if graph.l_predicate_edge(e, e_prime) {
+                        l_match_union.extend(l_match.l_match_with_node(e, e_prime, u.id()));
                     }
                 }
+                l_match_intersection = match l_match_intersection {
+                    Some(acc) => Some(acc.intersection(&l_match_union).copied().collect()),
+                    None => Some(l_match_union),
+                };
             }
+            l_match_intersection.is_some_and(|ids| ids.contains(&v.id()))
+        }).collect();
+        (u, res)
+    }).collect()
+}
 
-            if !all_in_vc {
-                q.push_back((u_id, v_id));      // 加入 Worklist
-                pi_retained.remove(&(u_id, v_id)); // Pi = Pi \ Q
+/// Whether `(u, v)` survives one refinement step of the `LMatch` simulation: some pair of
+/// hyperedges `e ∋ u`, `e' ∋ v` with `l_predicate_edge(e, e')` has every `u'` in its domain
+/// matched onto a node accepted by `in_sim`.
+#[allow(clippy::too_many_arguments)]
+fn l_match_supported<'a, H>(
+    graph: &'a H,
+    other: &'a H,
+    self_contained_hyperedge: &HyperedgeList<'a, H>,
+    other_contained_hyperedge: &HyperedgeList<'a, H>,
+    l_match: &impl LMatch<Edge = H::Edge>,
+    u: &'a H::Node,
+    v: &'a H::Node,
+    mut in_sim: impl FnMut(&'a H::Node) -> bool,
+) -> bool
+where H: Hypergraph<'a> + LPredicate<'a> + ContainedHyperedge<'a> {
+    for e in graph.contained_hyperedges(self_contained_hyperedge, u) {
+        for e_prime in other.contained_hyperedges(other_contained_hyperedge, v) {
+            if graph.l_predicate_edge(e, e_prime) && l_match.dom(e, e_prime).all(|u_prime| {
+                l_match.l_match_with_node(e, e_prime, *u_prime).iter().filter_map(|id| other.get_node_by_id(*id)).any(&mut in_sim)
+            }) {
+                return true;
             }
         }
-        pi = pi_retained;
+    }
+    false
+}
 
-        info!("3. 找出失效的 (u, v) 加入队列 Q");
+/// The hyperedges of a directed hypergraph containing each node, and the tail and head of every hyperedge as nodes.
+struct DirectedIncidence<'a, H: Hypergraph<'a>> {
+    src: HyperedgeList<'a, H>,
+    dst: HyperedgeList<'a, H>,
+    sides: HashMap<&'a H::Edge, Sides<'a, H>>,
+}
 
-        // ==========================================
-        // Phase 2: Cascade deletions via the queue
-        // ==========================================
-        while let Some((up_id, vp_id)) = q.pop_front() {
-            // 获取所有依赖于已删除节点对 (u', v') 的簇对 (Cu, Cv)
-            if let Some(dependent_clusters) = d_pair.get(&(up_id, vp_id)) {
-                for c_pair in dependent_clusters {
-                    // 如果簇对仍然被认为是有效的，现在它失效了
-                    if v_c.contains(c_pair) {
-                        v_c.remove(c_pair); // V_c = V_c \ {(Cu, Cv)}
+impl<'a, H> DirectedIncidence<'a, H>
+where H: ContainedDirectedHyperedge<'a>, H::Edge: DirectedHyperedge {
+    fn new(graph: &'a H) -> Self {
+        let ids: HashMap<usize, &'a H::Node> = graph.nodes().map(|node| (node.id(), node)).collect();
+        let nodes = |side: HashSet<usize>| side.iter().map(|id| ids[id]).collect::<Vec<_>>();
+        let sides = graph.hyperedges().map(|e| (e, (nodes(e.src()), nodes(e.dst())))).collect();
+        DirectedIncidence { src: graph.get_hyperedges_src(), dst: graph.get_hyperedges_dst(), sides }
+    }
 
-                        // 级联使依赖这个失效簇对的 (u, v) 失效
-                        if let Some(dependent_node_pairs) = d_cluster.get(c_pair) {
-                            for node_pair in dependent_node_pairs {
-                                if pi.contains(node_pair) {
-                                    pi.remove(node_pair);
-                                    q.push_back(*node_pair);
-                                }
-                            }
-                        }
-                    }
-                }
+    /// The hyperedges with `node` in their tail or head, each once.
+    fn contained(&self, graph: &'a H, node: &'a H::Node) -> Vec<&'a H::Edge> {
+        let mut edges: Vec<&'a H::Edge> = ContainedDirectedHyperedge::contained_hyperedges(graph, &self.src, node).collect();
+        for e in ContainedDirectedHyperedge::contained_hyperedges(graph, &self.dst, node) {
+            if !edges.contains(&e) {
+                edges.push(e);
             }
         }
+        edges
+    }
+}
 
-        info!("结束了主调用");
+/// For every hyperedge e containing `u`, the hyperedges e' containing `v` that may match it in the check of (u, v):
+/// those with l_predicate_edge(e, e') and `v` in the tail (head) of e' if `u` is in the tail (head) of e.
+fn directed_edge_candidates<'a, H>(
+    graph: &'a H,
+    other: &'a H,
+    pattern: &DirectedIncidence<'a, H>,
+    data: &DirectedIncidence<'a, H>,
+    u: &'a H::Node,
+    v: &'a H::Node,
+) -> EdgeCandidates<'a, H>
+where H: ContainedDirectedHyperedge<'a> + LPredicate<'a>, H::Edge: DirectedHyperedge {
+    let data_edges = data.contained(other, v);
+    pattern.contained(graph, u).into_iter().map(|e| {
+        let (src, dst) = &pattern.sides[e];
+        let e_primes = data_edges.iter().copied().filter(|&e_prime| {
+            let (src_prime, dst_prime) = &data.sides[e_prime];
+            graph.l_predicate_edge(e, e_prime)
+                && (!src.contains(&u) || src_prime.contains(&v))
+                && (!dst.contains(&u) || dst_prime.contains(&v))
+        }).collect();
+        (e, e_primes)
+    }).collect()
+}
 
-        // ==========================================
-        // Phase 3: Construct Output
-        // ==========================================
-        // 将基于 ID 的关系还原为引用的 HashMap
-        let mut result: HashMap<&'a Self::Node, HashSet<&'a Self::Node>> = 
-            self.nodes().map(|u| (u, HashSet::new())).collect();
+/// Whether every hyperedge of `candidates` has a candidate e' in which every node of its tail (head) has a node
+/// accepted by `in_sim` in the tail (head) of e'.
+fn directed_supported<'a, H: Hypergraph<'a>>(
+    pattern: &DirectedIncidence<'a, H>,
+    data: &DirectedIncidence<'a, H>,
+    candidates: &[(&'a H::Edge, Vec<&'a H::Edge>)],
+    in_sim: impl Fn(&'a H::Node, &'a H::Node) -> bool,
+) -> bool {
+    candidates.iter().all(|(e, e_primes)| {
+        let (src, dst) = &pattern.sides[*e];
+        e_primes.iter().any(|e_prime| {
+            let (src_prime, dst_prime) = &data.sides[*e_prime];
+            src.iter().all(|&u_prime| src_prime.iter().any(|&v_prime| in_sim(u_prime, v_prime)))
+                && dst.iter().all(|&u_prime| dst_prime.iter().any(|&v_prime| in_sim(u_prime, v_prime)))
+        })
+    })
+}
+
+type NodePair<'a, H> = (&'a <H as Hypergraph<'a>>::Node, &'a <H as Hypergraph<'a>>::Node);
+// The tail and the head of a directed hyperedge.
+type Sides<'a, H> = (Vec<&'a <H as Hypergraph<'a>>::Node>, Vec<&'a <H as Hypergraph<'a>>::Node>);
+// Every hyperedge containing u, with the hyperedges containing v that may match it.
+type EdgeCandidates<'a, H> = Vec<(&'a <H as Hypergraph<'a>>::Edge, Vec<&'a <H as Hypergraph<'a>>::Edge>)>;
+
+/// Demand-driven solver behind `get_simulation_recursive`.
+///
+/// A pair is assumed to hold while it is being decided, so cycles resolve to the greatest fixpoint.
+/// Every pair that read a still-holding pair is recorded as its dependent, and is re-decided once
+/// that pair turns out not to hold.
+struct RecursiveSimulation<'a, 's, H: Hypergraph<'a>, L> {
+    graph: &'a H,
+    other: &'a H,
+    self_contained_hyperedge: &'s HyperedgeList<'a, H>,
+    other_contained_hyperedge: &'s HyperedgeList<'a, H>,
+    l_match: &'s L,
+    candidates: &'s HashMap<&'a H::Node, HashSet<&'a H::Node>>,
+    memo: HashMap<NodePair<'a, H>, bool>,
+    dependents: HashMap<NodePair<'a, H>, HashSet<NodePair<'a, H>>>,
+}
 
-        for (u_id, v_id) in pi {
-            // 由于我们事先在字典中存过映射，这里可以安全取值
-            let u_node = id_to_u[&u_id];
-            let v_node = id_to_v[&v_id];
-            if let Some(set) = result.get_mut(u_node) {
-                set.insert(v_node);
+impl<'a, 's, H, L> RecursiveSimulation<'a, 's, H, L>
+where H: Hypergraph<'a> + LPredicate<'a> + ContainedHyperedge<'a>, L: LMatch<Edge = H::Edge> {
+    fn decide(&mut self, pair: NodePair<'a, H>, caller: Option<NodePair<'a, H>>) -> bool {
+        if let Some(&holds) = self.memo.get(&pair) {
+            if let (true, Some(caller)) = (holds, caller) {
+                self.dependents.entry(pair).or_default().insert(caller);
             }
+            return holds;
         }
 
-        result
+        let (u, v) = pair;
+        if !self.candidates.get(u).is_some_and(|sim_u| sim_u.contains(v)) {
+            self.memo.insert(pair, false);
+            return false;
+        }
+
+        self.memo.insert(pair, true);
+        if let Some(caller) = caller {
+            self.dependents.entry(pair).or_default().insert(caller);
+        }
+        if !self.check(pair) && self.memo[&pair] {
+            self.invalidate(pair);
+        }
+        self.memo[&pair]
     }
 
-    fn get_hyper_simulation_effect_by_id(&'a self, hc_map: &HashMap<(usize, usize), Vec<((usize, usize), HashSet<(usize, usize)>)>>) -> HashSet<(usize, usize)> {
-        // ==========================================
-        // hc_map 参数说明：
-        // ==========================================
-        // hc_map 是一个预计算的语义集群和 D-match 关系映射表，用于完全在 ID 空间内执行 Hyper Simulation 计算。
-        // 
-        // 结构：HashMap<(u_id, v_id), Vec<((cu_id, cv_id), D_match_set)>>
-        // 
-        // 键 (u_id, v_id)：
-        //   - 候选节点对，其中 u_id 属于 self，v_id 属于 other
-        //   - 代表可能参加 Hyper Simulation 的节点对
-        // 
-        // 值 Vec<((cu_id, cv_id), D_match_set)>：
-        //   - 该节点对相关的所有语义集群及其 D-match 关系
-        //   - (cu_id, cv_id)：语义集群对的 ID（u 侧和 v 侧）
-        //   - D_match_set：HashSet<(usize, usize)>，该集群对的 D-match 结果
-        //     即满足 d_match(cluster_u, cluster_v) 的所有节点对 (u'_id, v'_id)
-        // 
-        // 调用前的准备步骤（由调用者负责）：
-        //   1. 计算 type_same 的两个图之间所有可兼容的节点对
-        //   2. 对每个节点对，调用 delta.get_sematic_clusters() 获取语义集群
-        //   3. 对每个集群对，调用 d_match.d_match() 计算 D-match 集合
-        //   4. 构建此 hc_map 并传入
-        //
-        // 函数内执行：
-        //   - 完全基于 ID-based 的数据结构，不涉及具体的节点类型
-        //   - 执行三个阶段：初始化、V_C 构建、级联删除
-        //   - 返回最终的 Hyper Simulation 结果集合
-        
-        init_global_logger_once("logs/hyper-simulation.log");
+    fn check(&mut self, pair: NodePair<'a, H>) -> bool {
+        let (graph, other, l_match) = (self.graph, self.other, self.l_match);
+        let (self_contained_hyperedge, other_contained_hyperedge) = (self.self_contained_hyperedge, self.other_contained_hyperedge);
+        let (u, v) = pair;
+        l_match_supported(graph, other, self_contained_hyperedge, other_contained_hyperedge, l_match, u, v, |v_prime| {
+            self.decide((u, v_prime), Some(pair))
+        })
+    }
 
-        // Pi: 当前满足 Hyper Simulation 条件的 (u.id(), v.id()) 集合
-        let mut pi: HashSet<(usize, usize)> = HashSet::new();
+    fn invalidate(&mut self, pair: NodePair<'a, H>) {
+        info!("Deleting {} -> {}", pair.0.id(), pair.1.id());
+        self.memo.insert(pair, false);
+        let mut stack = vec![pair];
+        while let Some(removed) = stack.pop() {
+            for dependent in self.dependents.remove(&removed).unwrap_or_default() {
+                if !self.memo[&dependent] || self.check(dependent) {
+                    continue;
+                }
+                // The re-check may already have invalidated `dependent` through a nested cascade.
+                if self.memo[&dependent] {
+                    info!("Deleting {} -> {}", dependent.0.id(), dependent.1.id());
+                    self.memo.insert(dependent, false);
+                    stack.push(dependent);
+                }
+            }
+        }
+    }
+}
 
-        // ==========================================
-        // Phase 1: Initialize Pi from hc_map
-        // ==========================================
-        for &(u_id, v_id) in hc_map.keys() {
-            pi.insert((u_id, v_id));
+/// Where the hyper simulations log, and whether `get_hyper_simulation_naive`, `_strict` and the `_effect` ones record a
+/// trace; the ones on an `LMatch` and the directed ones only log. The default does neither, so that runs in parallel
+/// have no side effects.
+#[derive(Clone, Debug, Default)]
+pub struct HyperSimulationOptions {
+    /// The file of the global logger, with its directory created if needed. The logger is set up once per process,
+    /// so only the first file takes effect, and a logger set up before still receives the records without one.
+    pub log_file: Option<PathBuf>,
+    /// Whether the trace is returned.
+    pub trace: bool,
+    /// A file the trace is stored in. A failure to store it is logged and does not fail the simulation.
+    pub trace_file: Option<PathBuf>,
+}
+
+impl HyperSimulationOptions {
+    fn init_logger(&self) {
+        if let Some(log_file) = &self.log_file {
+            init_global_logger_once(log_file);
         }
+    }
 
-        info!("完成了 Pi 的初始化和 HC、D-match 的获取，Pi 大小: {}", pi.len());
+    fn records_trace(&self) -> bool {
+        self.trace || self.trace_file.is_some()
+    }
 
-        // A_cluster 对应的 D-match 缓存，避免重复计算
-        let mut a_cluster_d_match: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-        
-        // 依赖索引构建:
-        // D_cluster[(Cu, Cv)] -> { (u, v) \in Pi } 
-        let mut d_cluster: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-        // D_pair[(u', v')] -> { (Cu, Cv) \in A_cluster }
-        let mut d_pair: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
-
-        for (&(u_id, v_id), clusters) in hc_map.iter() {
-            for ((cu_id, cv_id), d_match_set) in clusters {
-                let c_pair = (*cu_id, *cv_id);
-                
-                // 填充 D_cluster
-                d_cluster.entry(c_pair).or_default().insert((u_id, v_id));
-
-                // 如果这是第一次遇到这个簇对，填充 D_pair
-                if !a_cluster_d_match.contains_key(&c_pair) {
-                    a_cluster_d_match.insert(c_pair, d_match_set.clone());
-
-                    for &(up_id, vp_id) in d_match_set {
-                        d_pair.entry((up_id, vp_id)).or_default().insert(c_pair);
-                    }
-                }
+    /// Stores `trace` in `trace_file` if any, and returns it if it is asked for.
+    fn finish_trace(&self, trace: HyperSimulationTrace) -> Option<HyperSimulationTrace> {
+        if let Some(trace_file) = &self.trace_file {
+            if let Err(e) = trace.store(trace_file) {
+                warn!("Failed to store the trace in {}: {}", trace_file.display(), e);
             }
         }
+        self.trace.then_some(trace)
+    }
+}
+
+/// The deletions of `get_hyper_simulation_naive`, `_strict` or `_effect`, in order, from the candidate pairs of the same
+/// type.
+///
+/// Every candidate pair not in the result is removed by exactly one event. A `Derivation` only names pairs that are
+/// not candidates or were removed by earlier events, so the removals can be replayed and explained.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct HyperSimulationTrace {
+    candidates: HashSet<(usize, usize)>,
+    events: Vec<HSEvent>
+}
 
-        info!("1. 初始化 Pi 并获取 HC 和 D-match");
+impl HyperSimulationTrace {
+    fn new() -> Self {
+        HyperSimulationTrace {
+            candidates: HashSet::new(),
+            events: Vec::new()
+        }
+    }
 
-        // 2. 初始化 V_C (Valid Clusters)
-        let mut v_c: HashSet<(usize, usize)> = HashSet::new();
-        for (c_pair, d_match_set) in &a_cluster_d_match {
-            // 条件 2.b: D-match 的所有元素都必须在当前的 Pi 中
-            if d_match_set.is_subset(&pi) {
-                v_c.insert(*c_pair);
-            }
+    /// Stores the trace in `path` with bincode, creating its directory if needed.
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
         }
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
 
-        info!("2. 初始化 V_C (Valid Clusters)");
+    /// Loads a trace stored by `store`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Ok(bincode::deserialize_from(&mut reader)?)
+    }
 
-        // 3. 找出失效的 (u, v) 加入队列 Q
-        let mut q: VecDeque<(usize, usize)> = VecDeque::new();
-        let mut pi_retained = pi.clone();
+    pub fn events(&self) -> &[HSEvent] {
+        &self.events
+    }
 
-        for &(u_id, v_id) in &pi {
-            let mut all_in_vc = true;
-            if let Some(clusters) = hc_map.get(&(u_id, v_id)) {
-                for (c_pair, _) in clusters {
-                    if !v_c.contains(c_pair) {
-                        all_in_vc = false;
-                        break;
-                    }
-                }
-            }
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
 
-            if !all_in_vc {
-                q.push_back((u_id, v_id));      // 加入 Worklist
-                pi_retained.remove(&(u_id, v_id)); // Pi = Pi \ Q
-            }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The pairs (u_id, v_id) of the same type, before any event.
+    pub fn candidates(&self) -> &HashSet<(usize, usize)> {
+        &self.candidates
+    }
+
+    /// Replays the events from the candidates, one at a time.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay { trace: self, next: 0, state: self.candidates.clone() }
+    }
+
+    /// The pairs still in the simulation after the event `index`.
+    pub fn state_after(&self, index: usize) -> HashSet<(usize, usize)> {
+        let mut replay = self.replay();
+        for _ in 0..=index {
+            replay.step();
         }
-        pi = pi_retained;
+        replay.state
+    }
 
-        info!("3. 找出失效的 (u, v) 加入队列 Q");
+    /// The pairs in the simulation after all events, i.e. the result.
+    pub fn simulation(&self) -> HashSet<(usize, usize)> {
+        let mut state = self.candidates.clone();
+        for event in &self.events {
+            state.remove(&event.pair());
+        }
+        state
+    }
 
-        // ==========================================
-        // Phase 2: Cascade deletions via the queue
-        // ==========================================
-        while let Some((up_id, vp_id)) = q.pop_front() {
-            // 获取所有依赖于已删除节点对 (u', v') 的簇对 (Cu, Cv)
-            if let Some(dependent_clusters) = d_pair.get(&(up_id, vp_id)) {
-                for c_pair in dependent_clusters {
-                    // 如果簇对仍然被认为是有效的，现在它失效了
-                    if v_c.contains(c_pair) {
-                        v_c.remove(c_pair); // V_c = V_c \ {(Cu, Cv)}
+    /// The index of the event removing `pair`, if any.
+    pub fn removal(&self, pair: (usize, usize)) -> Option<usize> {
+        self.events.iter().position(|event| event.pair() == pair)
+    }
 
-                        // 级联使依赖这个失效簇对的 (u, v) 失效
-                        if let Some(dependent_node_pairs) = d_cluster.get(c_pair) {
-                            for node_pair in dependent_node_pairs {
-                                if pi.contains(node_pair) {
-                                    pi.remove(node_pair);
-                                    q.push_back(*node_pair);
-                                }
-                            }
-                        }
-                    }
-                }
+    /// Why `pair` is not in the hyper simulation, or `None` if it is. The tree goes from the event removing `pair`
+    /// through the pairs it found missing down to base D-match failures and pairs that are not candidates.
+    pub fn why_not(&self, pair: (usize, usize)) -> Option<WhyNot<'_>> {
+        let removals: HashMap<(usize, usize), usize> = self.events.iter().enumerate().map(|(i, event)| (event.pair(), i)).collect();
+        if self.candidates.contains(&pair) && !removals.contains_key(&pair) {
+            return None;
+        }
+        let mut expanded = HashSet::new();
+        Some(self.derive(pair, &removals, &mut expanded))
+    }
+
+    // Every pair is expanded once, at its first occurrence in the tree; the later ones only name their event.
+    fn derive(&self, pair: (usize, usize), removals: &HashMap<(usize, usize), usize>, expanded: &mut HashSet<(usize, usize)>) -> WhyNot<'_> {
+        let event = removals.get(&pair).map(|&i| &self.events[i]);
+        let mut premises = Vec::new();
+        if let Some(HSEvent::Derivation { uncovered, .. }) = event {
+            if expanded.insert(pair) {
+                let mut uncovered: Vec<(usize, usize)> = uncovered.iter().copied().collect();
+                uncovered.sort_by_key(|p| (removals.get(p).copied(), *p));
+                premises = uncovered.into_iter().map(|p| self.derive(p, removals, expanded)).collect();
             }
         }
+        WhyNot { pair, event, premises }
+    }
 
-        info!("结束了主调用");
+    fn add_candidate(&mut self, pair: (usize, usize)) {
+        self.candidates.insert(pair);
+    }
 
-        // ==========================================
-        // Phase 3: Return ID-based Result
-        // ==========================================
-        pi
+    fn add_base_event(&mut self, pair: (usize, usize), cluster_pair: (usize, usize), d_match: HashSet<(usize, usize)>) {
+        self.events.push(HSEvent::Base { pair, cluster_pair, d_match });
+    }
+
+    fn add_unclustered_event(&mut self, pair: (usize, usize)) {
+        self.events.push(HSEvent::Unclustered { pair });
+    }
+
+    fn add_derivation_event(&mut self, pair: (usize, usize), round: usize, cluster_pair: (usize, usize), uncovered: HashSet<(usize, usize)>) {
+        self.events.push(HSEvent::Derivation { pair, round, cluster_pair, uncovered });
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-pub struct HyperSimulationTrace {
-    events: Vec<HSEvent>
+/// A replay of a `HyperSimulationTrace`: the state after the events stepped through so far.
+pub struct Replay<'t> {
+    trace: &'t HyperSimulationTrace,
+    next: usize,
+    state: HashSet<(usize, usize)>,
 }
 
-impl HyperSimulationTrace {
-    fn new() -> Self {
-        HyperSimulationTrace {
-            events: Vec::new()
-        }
+impl<'t> Replay<'t> {
+    /// Applies the next event and returns it, or `None` when all events are applied.
+    pub fn step(&mut self) -> Option<&'t HSEvent> {
+        let event = self.trace.events.get(self.next)?;
+        self.state.remove(&event.pair());
+        self.next += 1;
+        Some(event)
     }
 
-    fn add_base_event(&mut self, sc_id: usize, d_match: HashSet<(usize, usize)>) {
-        let event = HSEvent::Base(sc_id, d_match);
-        self.events.push(event);
+    /// The number of events applied.
+    pub fn position(&self) -> usize {
+        self.next
     }
-    fn add_derivation_event(&mut self, sc_id: usize, uncoverd: HashSet<(usize, usize)>) {
-        let event = HSEvent::Derivation(sc_id, uncoverd);
-        self.events.push(event);
+
+    /// The pairs in the simulation after the events applied.
+    pub fn state(&self) -> &HashSet<(usize, usize)> {
+        &self.state
     }
 }
 
+/// A derivation of a pair (u_id, v_id) not being in the hyper simulation.
+///
+/// `event` is the removal of the pair, or `None` if it was never a candidate. The premises of a `Derivation` are
+/// the missing pairs of its D-match, in the order of their removal; they are empty for the leaves and for a pair
+/// already derived elsewhere in the tree.
+#[derive(Debug)]
+pub struct WhyNot<'t> {
+    pub pair: (usize, usize),
+    pub event: Option<&'t HSEvent>,
+    pub premises: Vec<WhyNot<'t>>,
+}
+
 impl IntoIterator for HyperSimulationTrace {
     type Item = HSEvent;
     type IntoIter = std::vec::IntoIter<HSEvent>;
@@ -960,23 +1412,45 @@ impl<'a> IntoIterator for &'a HyperSimulationTrace {
 
 impl TraceLog for HyperSimulationTrace {
     fn store_trace_file(self, filename: &'static str) -> Result<(), Box<dyn Error>> {
-        // use bincode to save the HyperSimulationTrace.
-        let file = File::create(filename)?;
-        let mut writer = BufWriter::new(file);
-        bincode::serialize_into(&mut writer, &self)?;
-        Ok(())
+        self.store(filename)
     }
     
     fn get_trace(filename: &'static str) -> Result<Self, Box<dyn Error>> {
-        let file = File::open(filename)?;
-        let mut reader = BufReader::new(file);
-        let file_decoded: HyperSimulationTrace = bincode::deserialize_from(&mut reader)?;
-        Ok(file_decoded)
+        Self::load(filename)
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub enum HSEvent {
-    Base(usize, HashSet<(usize, usize)>), // current D-Match
-    Derivation(usize, HashSet<(usize, usize)>) // D-Match \ Sim
-}
\ No newline at end of file
+    /// In the initial round, `pair` is not in the D-match of its `cluster_pair`.
+    Base { pair: (usize, usize), cluster_pair: (usize, usize), d_match: HashSet<(usize, usize)> },
+    /// In the initial round of `get_hyper_simulation_strict`, `pair` has no semantic cluster.
+    Unclustered { pair: (usize, usize) },
+    /// In `round`, the D-match of the `cluster_pair` of `pair` has the `uncovered` pairs no longer in the simulation.
+    Derivation { pair: (usize, usize), round: usize, cluster_pair: (usize, usize), uncovered: HashSet<(usize, usize)> },
+}
+
+impl HSEvent {
+    /// The removed pair (u_id, v_id).
+    pub fn pair(&self) -> (usize, usize) {
+        match self {
+            HSEvent::Base { pair, .. } | HSEvent::Unclustered { pair } | HSEvent::Derivation { pair, .. } => *pair,
+        }
+    }
+
+    /// The round of the removal, 0 for the initial one.
+    pub fn round(&self) -> usize {
+        match self {
+            HSEvent::Derivation { round, .. } => *round,
+            _ => 0,
+        }
+    }
+
+    /// The responsible cluster pair (Cu, Cv), if any.
+    pub fn cluster_pair(&self) -> Option<(usize, usize)> {
+        match self {
+            HSEvent::Base { cluster_pair, .. } | HSEvent::Derivation { cluster_pair, .. } => Some(*cluster_pair),
+            HSEvent::Unclustered { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_base::interfaces::labeled::Labeled;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The pairs `(u.id(), v.id())` that entered or left the simulation relation after an update.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SimulationDelta {
+    pub added: HashSet<(usize, usize)>,
+    pub removed: HashSet<(usize, usize)>,
+}
+
+impl SimulationDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The maximal simulation of a fixed pattern in a data graph that keeps changing.
+///
+/// The data graph is copied at construction and then updated through `insert_node`, `delete_node`,
+/// `insert_edge` and `delete_edge`, where data nodes are referred to by their id. An update naming
+/// an absent node, or deleting an absent edge, leaves the graph unchanged and returns an empty delta.
+/// The relation is the one of `get_simulation_hhk`. For every pattern node `u` and data node `x`, count(u, x) is the
+/// number of edges from `x` into sim(u), so that a pair (u', x) is supported iff count(u, x) > 0 for
+/// every child `u` of `u'`. Deletions only remove pairs whose support dropped to zero; an insertion
+/// can only add pairs, and only for the ancestors of the new edge that have a missing candidate pair.
+pub struct IncrementalSimulation<'a, T: Graph<'a>> {
+    pattern: &'a T,
+    pattern_nodes: Vec<&'a T::Node>,
+    pattern_post: Vec<Vec<usize>>,
+    pattern_pre: Vec<Vec<usize>>,
+    nodes: HashMap<usize, T::Node>,
+    // post[x][y] is the number of edges (x, y), and pre[y][x] the same number.
+    post: HashMap<usize, HashMap<usize, usize>>,
+    pre: HashMap<usize, HashMap<usize, usize>>,
+    sim: Vec<HashSet<usize>>,
+    count: HashMap<(usize, usize), usize>,
+}
+
+impl<'a, T> IncrementalSimulation<'a, T>
+where
+    T: Graph<'a> + Labeled<'a>,
+    T::Node: 'a,
+{
+    pub fn new(pattern: &'a T, data: &'a T) -> Self {
+        let pattern_nodes: Vec<&'a T::Node> = pattern.nodes().collect();
+        let index: HashMap<&T::Node, usize> = pattern_nodes.iter().enumerate().map(|(i, &u)| (u, i)).collect();
+        let mut pattern_post = vec![Vec::new(); pattern_nodes.len()];
+        let mut pattern_pre = vec![Vec::new(); pattern_nodes.len()];
+        for (u, u_prime) in pattern.get_edges_pair() {
+            pattern_post[index[u]].push(index[u_prime]);
+            pattern_pre[index[u_prime]].push(index[u]);
+        }
+
+        let mut simulation = IncrementalSimulation {
+            pattern,
+            sim: vec![HashSet::new(); pattern_nodes.len()],
+            pattern_nodes,
+            pattern_post,
+            pattern_pre,
+            nodes: data.nodes().map(|v| (v.id(), v.clone())).collect(),
+            post: HashMap::new(),
+            pre: HashMap::new(),
+            count: HashMap::new(),
+        };
+        for (v, v_prime) in data.get_edges_pair() {
+            simulation.add_edge(v.id(), v_prime.id());
+        }
+
+        // Start from all label-compatible pairs and refine them, as in get_simulation_hhk.
+        let candidates: Vec<(usize, usize)> = simulation.label_candidates(simulation.nodes.keys().copied().collect());
+        for &(u, x) in &candidates {
+            simulation.insert_pair(u, x);
+        }
+        simulation.refine(candidates.into_iter().collect());
+        simulation
+    }
+
+    /// The current relation, from the nodes of the pattern to the nodes of the data graph.
+    pub fn relation(&self) -> HashMap<&'a T::Node, HashSet<&T::Node>> {
+        self.pattern_nodes.iter().zip(&self.sim).map(|(&u, sim_u)| {
+            (u, sim_u.iter().map(|x| &self.nodes[x]).collect())
+        }).collect()
+    }
+
+    /// Adds an isolated node to the data graph. A node already present is left unchanged.
+    pub fn insert_node(&mut self, node: T::Node) -> SimulationDelta {
+        let x = node.id();
+        if self.nodes.contains_key(&x) {
+            return SimulationDelta::default();
+        }
+        self.nodes.insert(x, node);
+
+        // Without children, x can only simulate pattern nodes without children.
+        let mut delta = SimulationDelta::default();
+        for (u, _) in self.label_candidates(vec![x]) {
+            if self.pattern_post[u].is_empty() {
+                self.insert_pair(u, x);
+                delta.added.insert((self.pattern_nodes[u].id(), x));
+            }
+        }
+        delta
+    }
+
+    /// Removes the node with id `x` and all of its edges from the data graph.
+    pub fn delete_node(&mut self, x: usize) -> SimulationDelta {
+        let mut delta = SimulationDelta::default();
+        if !self.nodes.contains_key(&x) {
+            return delta;
+        }
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for (&y, &multiplicity) in self.post.get(&x).into_iter().flatten() {
+            edges.extend(std::iter::repeat_n((x, y), multiplicity));
+        }
+        for (&y, &multiplicity) in self.pre.get(&x).into_iter().flatten() {
+            if y != x {
+                edges.extend(std::iter::repeat_n((y, x), multiplicity));
+            }
+        }
+        for (source, target) in edges {
+            delta.removed.extend(self.delete_edge(source, target).removed);
+        }
+
+        for u in 0..self.pattern_nodes.len() {
+            if self.sim[u].remove(&x) {
+                delta.removed.insert((self.pattern_nodes[u].id(), x));
+            }
+            self.count.remove(&(u, x));
+        }
+        self.nodes.remove(&x);
+        self.post.remove(&x);
+        self.pre.remove(&x);
+        delta
+    }
+
+    /// Adds the edge `(x, y)` to the data graph. Nothing changes, and the delta is empty, if `x` or `y` is not present.
+    pub fn insert_edge(&mut self, x: usize, y: usize) -> SimulationDelta {
+        if !self.nodes.contains_key(&x) || !self.nodes.contains_key(&y) {
+            return SimulationDelta::default();
+        }
+        self.add_edge(x, y);
+        for u in 0..self.pattern_nodes.len() {
+            if self.sim[u].contains(&y) {
+                *self.count.entry((u, x)).or_default() += 1;
+            }
+        }
+
+        // Only ancestors of x may gain pairs, and only through nodes that are missing some candidate pair:
+        // a node whose candidates are all in the relation already gives its parents no new support.
+        let mut candidates: Vec<(usize, usize)> = Vec::new();
+        let mut visited: HashSet<usize> = HashSet::from([x]);
+        let mut queue = VecDeque::from([x]);
+        while let Some(z) = queue.pop_front() {
+            let missing: Vec<(usize, usize)> = self.label_candidates(vec![z]).into_iter()
+                .filter(|&(u, z)| !self.sim[u].contains(&z))
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            candidates.extend(missing);
+            for &z_prime in self.pre.get(&z).into_iter().flat_map(|pre| pre.keys()) {
+                if visited.insert(z_prime) {
+                    queue.push_back(z_prime);
+                }
+            }
+        }
+
+        // The old relation is still a simulation, so the refinement only ever removes the new candidates.
+        for &(u, z) in &candidates {
+            self.insert_pair(u, z);
+        }
+        let removed = self.refine(candidates.iter().copied().collect());
+        let mut delta = SimulationDelta::default();
+        for (u, z) in candidates {
+            if !removed.contains(&(u, z)) {
+                delta.added.insert((self.pattern_nodes[u].id(), z));
+            }
+        }
+        delta
+    }
+
+    /// Removes one edge `(x, y)` from the data graph, if there is one.
+    pub fn delete_edge(&mut self, x: usize, y: usize) -> SimulationDelta {
+        let mut delta = SimulationDelta::default();
+        if !self.remove_edge(x, y) {
+            return delta;
+        }
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        for u in 0..self.pattern_nodes.len() {
+            if self.sim[u].contains(&y) && self.decrement(u, x, 1) {
+                queue.extend(self.pattern_pre[u].iter().map(|&u_prime| (u_prime, x)));
+            }
+        }
+        for (u, z) in self.refine(queue) {
+            delta.removed.insert((self.pattern_nodes[u].id(), z));
+        }
+        delta
+    }
+
+    /// The pairs (u, x) with label_same(u, x), for the pattern nodes u and the given data nodes x.
+    fn label_candidates(&self, xs: Vec<usize>) -> Vec<(usize, usize)> {
+        xs.into_iter().flat_map(|x| {
+            let node = &self.nodes[&x];
+            self.pattern_nodes.iter().enumerate()
+                .filter(move |(_, &u)| self.pattern.label_same(u, node))
+                .map(move |(u, _)| (u, x))
+        }).collect()
+    }
+
+    fn add_edge(&mut self, x: usize, y: usize) {
+        *self.post.entry(x).or_default().entry(y).or_default() += 1;
+        *self.pre.entry(y).or_default().entry(x).or_default() += 1;
+    }
+
+    fn remove_edge(&mut self, x: usize, y: usize) -> bool {
+        let Some(multiplicity) = self.post.get_mut(&x).and_then(|post| post.get_mut(&y)) else {
+            return false;
+        };
+        *multiplicity -= 1;
+        if *multiplicity == 0 {
+            self.post.get_mut(&x).unwrap().remove(&y);
+        }
+        let multiplicity = self.pre.get_mut(&y).unwrap().get_mut(&x).unwrap();
+        *multiplicity -= 1;
+        if *multiplicity == 0 {
+            self.pre.get_mut(&y).unwrap().remove(&x);
+        }
+        true
+    }
+
+    /// Decreases count(u, x) by `by`, returning whether it dropped to zero.
+    fn decrement(&mut self, u: usize, x: usize, by: usize) -> bool {
+        let count = self.count.get_mut(&(u, x)).unwrap();
+        *count -= by;
+        if *count == 0 {
+            self.count.remove(&(u, x));
+            return true;
+        }
+        false
+    }
+
+    fn insert_pair(&mut self, u: usize, x: usize) {
+        if self.sim[u].insert(x) {
+            for (&z, &multiplicity) in self.pre.get(&x).into_iter().flatten() {
+                *self.count.entry((u, z)).or_default() += multiplicity;
+            }
+        }
+    }
+
+    fn supported(&self, u: usize, x: usize) -> bool {
+        self.pattern_post[u].iter().all(|&u_prime| self.count.contains_key(&(u_prime, x)))
+    }
+
+    /// Removes the unsupported pairs reachable from `queue`, cascading to the parents
+    /// whose last child in sim(u) was removed. Returns the removed pairs.
+    fn refine(&mut self, mut queue: VecDeque<(usize, usize)>) -> HashSet<(usize, usize)> {
+        let mut removed = HashSet::new();
+        while let Some((u, x)) = queue.pop_front() {
+            if !self.sim[u].contains(&x) || self.supported(u, x) {
+                continue;
+            }
+            self.sim[u].remove(&x);
+            removed.insert((u, x));
+            let parents: Vec<(usize, usize)> = self.pre.get(&x).into_iter().flatten().map(|(&z, &m)| (z, m)).collect();
+            for (z, multiplicity) in parents {
+                if self.decrement(u, z, multiplicity) {
+                    queue.extend(self.pattern_pre[u].iter().map(|&u_prime| (u_prime, z)));
+                }
+            }
+        }
+        removed
+    }
+}
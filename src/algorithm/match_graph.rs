@@ -0,0 +1,162 @@
+use graph_base::interfaces::graph::Graph;
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// The part of a data graph that matches a pattern under a simulation relation: the data edges (v, v')
+/// matching a pattern edge (u, u') with v ∈ sim(u) and v' ∈ sim(u'), and their ends. A node of the data graph
+/// that only matches pattern nodes without any edge is a node of the match graph as well.
+///
+/// Every node points back to the pattern nodes it matches in it, and every edge to the pattern edges.
+pub struct SimulationMatchGraph<'a, N> {
+    nodes: Vec<&'a N>,
+    edges: Vec<(&'a N, &'a N)>,
+    pattern_nodes: HashMap<&'a N, Vec<&'a N>>,
+    pattern_edges: HashMap<(&'a N, &'a N), Vec<(&'a N, &'a N)>>,
+}
+
+pub trait ResultGraph<'a>: Graph<'a> {
+    /// The match graph of `sim`, a relation from the nodes of `self` to the nodes of `other`,
+    /// such as the result of `get_simulation_inter`. Nodes and edges are in the order of `other`.
+    fn get_match_graph(&'a self, other: &'a Self, sim: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> SimulationMatchGraph<'a, Self::Node>;
+}
+
+impl<'a, T: Graph<'a>> ResultGraph<'a> for T {
+    fn get_match_graph(&'a self, other: &'a Self, sim: &HashMap<&'a T::Node, HashSet<&'a T::Node>>) -> SimulationMatchGraph<'a, T::Node> {
+        // matched(v) := {u | v ∈ sim(u)}
+        let mut matched: HashMap<&T::Node, HashSet<&T::Node>> = HashMap::new();
+        for (&u, sim_u) in sim {
+            for &v in sim_u {
+                matched.entry(v).or_default().insert(u);
+            }
+        }
+        let mut post: HashMap<&T::Node, Vec<&T::Node>> = HashMap::new();
+        let mut isolated: HashSet<&T::Node> = self.nodes().collect();
+        for (u, u_prime) in self.get_edges_pair() {
+            post.entry(u).or_default().push(u_prime);
+            isolated.remove(u);
+            isolated.remove(u_prime);
+        }
+
+        let mut graph = SimulationMatchGraph::new();
+        let mut roles: HashMap<&T::Node, HashSet<&T::Node>> = HashMap::new();
+        for (v, v_prime) in other.get_edges_pair() {
+            let (Some(us), Some(us_prime)) = (matched.get(v), matched.get(v_prime)) else {
+                continue;
+            };
+            let pattern_edges: Vec<_> = us.iter()
+                .flat_map(|&u| post.get(u).into_iter().flatten().filter(|&u_prime| us_prime.contains(u_prime)).map(move |&u_prime| (u, u_prime)))
+                .collect();
+            if pattern_edges.is_empty() {
+                continue;
+            }
+            for &(u, u_prime) in &pattern_edges {
+                roles.entry(v).or_default().insert(u);
+                roles.entry(v_prime).or_default().insert(u_prime);
+            }
+            let known = graph.pattern_edges.entry((v, v_prime)).or_default();
+            if known.is_empty() {
+                graph.edges.push((v, v_prime));
+            }
+            for pair in pattern_edges {
+                if !known.contains(&pair) {
+                    known.push(pair);
+                }
+            }
+        }
+        for (&v, us) in &matched {
+            roles.entry(v).or_default().extend(us.iter().filter(|u| isolated.contains(*u)));
+        }
+
+        for v in other.nodes() {
+            if let Some(us) = roles.remove(v).filter(|us| !us.is_empty()) {
+                graph.nodes.push(v);
+                graph.pattern_nodes.insert(v, self.nodes().filter(|u| us.contains(u)).collect());
+            }
+        }
+        graph
+    }
+}
+
+impl<'a, N: Eq + Hash> SimulationMatchGraph<'a, N> {
+    fn new() -> Self {
+        SimulationMatchGraph { nodes: Vec::new(), edges: Vec::new(), pattern_nodes: HashMap::new(), pattern_edges: HashMap::new() }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &'a N> + '_ {
+        self.nodes.iter().copied()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&'a N, &'a N)> + '_ {
+        self.edges.iter().copied()
+    }
+
+    pub fn contains_node(&self, v: &N) -> bool {
+        self.pattern_nodes.contains_key(v)
+    }
+
+    pub fn contains_edge(&self, v: &'a N, v_prime: &'a N) -> bool {
+        self.pattern_edges.contains_key(&(v, v_prime))
+    }
+
+    /// The pattern nodes that `v` matches in the match graph, in the order of the pattern.
+    pub fn pattern_nodes(&self, v: &N) -> &[&'a N] {
+        self.pattern_nodes.get(v).map_or(&[], |us| us.as_slice())
+    }
+
+    /// The pattern edges that the edge (v, v') matches.
+    pub fn pattern_edges(&self, v: &'a N, v_prime: &'a N) -> &[(&'a N, &'a N)] {
+        self.pattern_edges.get(&(v, v_prime)).map_or(&[], |edges| edges.as_slice())
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The weakly connected components, each an individual match, in the order of their first node.
+    pub fn components(&self) -> Vec<SimulationMatchGraph<'a, N>> {
+        let index: HashMap<&N, usize> = self.nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        for &(v, v_prime) in &self.edges {
+            let (i, j) = (index[v], index[v_prime]);
+            neighbors[i].push(j);
+            neighbors[j].push(i);
+        }
+
+        let mut component = vec![usize::MAX; self.nodes.len()];
+        let mut components: Vec<SimulationMatchGraph<'a, N>> = Vec::new();
+        for start in 0..self.nodes.len() {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            component[start] = components.len();
+            let mut stack = vec![start];
+            while let Some(i) = stack.pop() {
+                for &j in &neighbors[i] {
+                    if component[j] == usize::MAX {
+                        component[j] = components.len();
+                        stack.push(j);
+                    }
+                }
+            }
+            components.push(SimulationMatchGraph::new());
+        }
+
+        for (i, &v) in self.nodes.iter().enumerate() {
+            let graph = &mut components[component[i]];
+            graph.nodes.push(v);
+            graph.pattern_nodes.insert(v, self.pattern_nodes[v].clone());
+        }
+        for &(v, v_prime) in &self.edges {
+            let graph = &mut components[component[index[v]]];
+            graph.edges.push((v, v_prime));
+            graph.pattern_edges.insert((v, v_prime), self.pattern_edges[&(v, v_prime)].clone());
+        }
+        components
+    }
+}
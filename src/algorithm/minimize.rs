@@ -0,0 +1,84 @@
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_base::interfaces::labeled::{Label, Labeled};
+
+use std::collections::{HashMap, HashSet};
+
+use crate::algorithm::simulation::Simulation;
+
+pub trait SimulationEquivalence<'a>: Simulation<'a> {
+    /// The classes of simulation-equivalent nodes, u and w being equivalent when each simulates the other
+    /// in `get_simulation`. Equivalent pattern nodes have the same matches in every data graph.
+    /// The classes and their nodes are in the order of `nodes()`, so the first node of a class is its representative.
+    fn get_simulation_classes(&'a self) -> Vec<Vec<&'a Self::Node>>;
+}
+
+impl<'a, T: Simulation<'a, Node = N>, N: 'a + Eq + std::hash::Hash> SimulationEquivalence<'a> for T
+where
+    T: Graph<'a, Node = N>,
+{
+    fn get_simulation_classes(&'a self) -> Vec<Vec<&'a N>> {
+        let sim = self.get_simulation();
+        let mut class_of: HashMap<&N, usize> = HashMap::new();
+        let mut classes: Vec<Vec<&N>> = Vec::new();
+        for u in self.nodes() {
+            if class_of.contains_key(u) {
+                continue;
+            }
+            let class: Vec<&N> = self.nodes().filter(|&w| sim[u].contains(w) && sim[w].contains(u)).collect();
+            for &w in &class {
+                class_of.insert(w, classes.len());
+            }
+            classes.push(class);
+        }
+        classes
+    }
+}
+
+pub trait MinimizePattern {
+    /// The smallest pattern with the same matches: the quotient by `get_simulation_classes`, where each class is its
+    /// representative and has an edge to another class if one of its nodes has an edge to one of that class, without
+    /// the redundant edges. An edge (c, d) is redundant when c has an edge to another class that simulates d,
+    /// whose matches are then always matches of d.
+    ///
+    /// In every data graph, the matches of a node of the minimized pattern are the matches of each node of its class.
+    fn minimize_pattern(&self) -> Self;
+}
+
+impl MinimizePattern for StandardLabeledGraph {
+    fn minimize_pattern(&self) -> Self {
+        let classes = self.get_simulation_classes();
+        let class_of: HashMap<usize, usize> = classes.iter().enumerate()
+            .flat_map(|(c, class)| class.iter().map(move |u| (u.id(), c)))
+            .collect();
+
+        let mut quotient = StandardLabeledGraph::new();
+        for class in &classes {
+            quotient.add_node(class[0].id() as u64, self.get_label(class[0]).label().to_string());
+        }
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for (u, u_prime) in self.get_edges_pair() {
+            let edge = (class_of[&u.id()], class_of[&u_prime.id()]);
+            if !edges.contains(&edge) {
+                edges.push(edge);
+                quotient.add_edge(classes[edge.0][0].id() as u64, classes[edge.1][0].id() as u64);
+            }
+        }
+
+        // In the quotient, simulation is an order: d is redundant for c if c has another child that simulates it.
+        let sim: HashMap<usize, HashSet<usize>> = quotient.get_simulation().into_iter()
+            .map(|(u, sim_u)| (class_of[&u.id()], sim_u.into_iter().map(|w| class_of[&w.id()]).collect()))
+            .collect();
+        let mut minimized = StandardLabeledGraph::new();
+        for class in &classes {
+            minimized.add_node(class[0].id() as u64, self.get_label(class[0]).label().to_string());
+        }
+        for &(c, d) in &edges {
+            let redundant = edges.iter().any(|&(x, e)| x == c && e != d && sim[&d].contains(&e));
+            if !redundant {
+                minimized.add_edge(classes[c][0].id() as u64, classes[d][0].id() as u64);
+            }
+        }
+        minimized
+    }
+}
@@ -1,4 +1,18 @@
-
 pub mod simulation;
 pub mod hyper_simulation;
-pub mod bounded;
\ No newline at end of file
+pub mod bounded;
+pub(crate) mod dense;
+pub(crate) mod reach;
+pub mod strong;
+pub mod bisimulation;
+pub mod incremental;
+pub mod relation;
+pub mod explain;
+pub mod regular;
+pub mod path;
+pub mod fractional;
+pub mod topk;
+pub mod match_graph;
+pub mod minimize;
+pub mod containment;
+pub mod compress;
@@ -0,0 +1,129 @@
+use graph_base::interfaces::labeled::Labeled;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::algorithm::bounded::{Bound, Bounded};
+use crate::algorithm::dense::{label_candidates, refine_reach, to_relation, BitSet, Csr, DenseGraph};
+use crate::utils::parallel::{par_map, MaybeSync};
+
+/// Which edges of a data path must have the label of the pattern edge it matches, by `edge_label_same`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathLabels {
+    /// Every edge of the path.
+    All,
+    /// The first edge; the others may have any label.
+    First,
+    /// The last edge; the others may have any label.
+    Last,
+    /// At least one edge.
+    Any,
+}
+
+impl PathLabels {
+    /// The state after an edge that has the label (`matches`) or not, from the state `accepted`, which is `None`
+    /// before the first edge and otherwise whether the path so far is accepted. `None` when no extension is.
+    fn step(&self, accepted: Option<bool>, matches: bool) -> Option<bool> {
+        match self {
+            PathLabels::All => matches.then_some(true),
+            PathLabels::First => if accepted.is_none() { matches.then_some(true) } else { Some(true) },
+            PathLabels::Last => Some(matches),
+            PathLabels::Any => Some(matches || accepted == Some(true)),
+        }
+    }
+}
+
+pub trait PathSimulation<'a> {
+    type Node: 'a;
+
+    /// `get_simulation_of_node_edge`, where a pattern edge (u, u') may be matched by a data path of one or more edges
+    /// instead of a single edge: its length must be within `get_bound_range(u, u')` and its edges must satisfy `labels`.
+    fn get_simulation_of_node_path(&'a self, other: &'a Self, labels: PathLabels) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+}
+
+impl<'a, T> PathSimulation<'a> for T
+where
+    T: Bounded<'a> + Labeled<'a> + MaybeSync,
+    T::Node: 'a + MaybeSync,
+    T::Edge: MaybeSync,
+{
+    type Node = T::Node;
+
+    fn get_simulation_of_node_path(&'a self, other: &'a Self, labels: PathLabels) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+
+        // dec(e, v) := {v' | a path v/.../v' matches the pattern edge e}
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut dec: Vec<Vec<Vec<usize>>> = Vec::new();
+        for u in 0..pattern.len() {
+            for (&u_prime, &u_edge) in pattern.post.neighbors(u).iter().zip(pattern.post.edges(u)) {
+                let u_prime = u_prime as usize;
+                let bound = self.get_bound_range(pattern.nodes[u], pattern.nodes[u_prime]);
+                let u_edge = pattern.edges[u_edge as usize];
+                let matches = |v_edge: u32| self.edge_label_same(u_edge, data.edges[v_edge as usize]);
+                edges.push((u, u_prime));
+                dec.push(par_map((0..data.len()).collect(), |v| path_ends(&data.post, v, bound, labels, &matches)));
+            }
+        }
+
+        let mut sim = label_candidates(self, &pattern, &data);
+        let dec: Vec<&[Vec<usize>]> = dec.iter().map(|dec_e| dec_e.as_slice()).collect();
+        refine_reach(&edges, &dec, &mut sim);
+        to_relation(&pattern, &data, &sim)
+    }
+}
+
+/// The ends of the paths from `v` along `post` of at least one edge, whose length is within `bound` and whose
+/// edges satisfy `labels`, where `matches` tells whether a data edge has the label of the pattern edge.
+fn path_ends(post: &Csr, v: usize, bound: Bound, labels: PathLabels, matches: &impl Fn(u32) -> bool) -> Vec<usize> {
+    let n = post.len();
+    let (lo, hi) = bound.limits();
+    let mut ends = BitSet::new(n);
+    if lo <= 1 {
+        // Only the shortest way to each state matters: a BFS over the nodes and whether the path is accepted.
+        let hi = hi.unwrap_or(usize::MAX);
+        let mut visited: HashSet<(usize, bool)> = HashSet::new();
+        let mut queue = VecDeque::from([(v, None, 0)]);
+        while let Some((x, accepted, length)) = queue.pop_front() {
+            if length == hi {
+                continue;
+            }
+            for (&y, &edge) in post.neighbors(x).iter().zip(post.edges(x)) {
+                let Some(accepted) = labels.step(accepted, matches(edge)) else {
+                    continue;
+                };
+                if visited.insert((y as usize, accepted)) {
+                    if accepted {
+                        ends.insert(y as usize);
+                    }
+                    queue.push_back((y as usize, Some(accepted), length + 1));
+                }
+            }
+        }
+    } else {
+        // With a least length, the paths are followed layer by layer, as in `dense::walks`.
+        let hi = hi.expect("A bound with a least length has a greatest one");
+        let mut layer: HashSet<(usize, Option<bool>)> = HashSet::from([(v, None)]);
+        for length in 1..=hi {
+            let mut next: HashSet<(usize, Option<bool>)> = HashSet::new();
+            for &(x, accepted) in &layer {
+                for (&y, &edge) in post.neighbors(x).iter().zip(post.edges(x)) {
+                    if let Some(accepted) = labels.step(accepted, matches(edge)) {
+                        next.insert((y as usize, Some(accepted)));
+                    }
+                }
+            }
+            if length >= lo {
+                for &(y, accepted) in &next {
+                    if accepted == Some(true) {
+                        ends.insert(y);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            layer = next;
+        }
+    }
+    ends.iter().collect()
+}
@@ -0,0 +1,214 @@
+use graph_base::interfaces::graph::Graph;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::algorithm::bounded::Bound;
+use crate::algorithm::dense::{intersects3, walks, BitSet, Csr, DenseGraph};
+
+/// The bounded reachability queries of bounded simulation on the data graph, on dense indices.
+pub(crate) trait Reach {
+    /// Whether a node within `bound` steps from `v` is in both `a` and `b`.
+    fn descendants_meet(&self, v: usize, bound: Bound, a: &BitSet, b: &BitSet) -> bool;
+
+    /// The nodes of `a` from which `v` is within `bound` steps, in increasing order.
+    fn ancestors_in(&self, v: usize, bound: Bound, a: &BitSet) -> Vec<usize>;
+}
+
+/// The reachability bitsets of every node, for each bound.
+pub(crate) struct FullReach {
+    ancestors: HashMap<Bound, Vec<BitSet>>,
+    descendants: HashMap<Bound, Vec<BitSet>>,
+}
+
+impl FullReach {
+    pub(crate) fn new<'a, T: Graph<'a>>(data: &DenseGraph<'a, T>, bounds: impl IntoIterator<Item = Bound>) -> Self {
+        let mut reach = FullReach { ancestors: HashMap::new(), descendants: HashMap::new() };
+        for bound in bounds {
+            reach.ancestors.entry(bound).or_insert_with(|| data.reach(&data.pre, bound));
+            reach.descendants.entry(bound).or_insert_with(|| data.reach(&data.post, bound));
+        }
+        reach
+    }
+}
+
+impl Reach for FullReach {
+    fn descendants_meet(&self, v: usize, bound: Bound, a: &BitSet, b: &BitSet) -> bool {
+        intersects3(&self.descendants[&bound][v], a, b)
+    }
+
+    fn ancestors_in(&self, v: usize, bound: Bound, a: &BitSet) -> Vec<usize> {
+        let mut ancestors = self.ancestors[&bound][v].clone();
+        ancestors.intersect_with(a);
+        ancestors.iter().collect()
+    }
+}
+
+/// BFS frontiers truncated at `depth`, computed for the queried nodes only and cached per source.
+/// With landmarks, a query is first decided on their distances, and only falls back to a BFS when they do not tell.
+/// The bounds with a least length are not decided by distances, and their paths are cached per source instead.
+pub(crate) struct TruncatedReach<'g> {
+    post: &'g Csr,
+    pre: &'g Csr,
+    n: usize,
+    depth: usize,
+    // The nodes within `depth` steps of each source queried so far, with their distance, in BFS order.
+    forward: RefCell<HashMap<usize, Vec<(usize, usize)>>>,
+    backward: RefCell<HashMap<usize, Vec<(usize, usize)>>>,
+    walks: RefCell<HashMap<(usize, bool, Bound), BitSet>>,
+    landmarks: Option<Landmarks>,
+}
+
+impl<'g> TruncatedReach<'g> {
+    pub(crate) fn new<'a, T: Graph<'a>>(data: &'g DenseGraph<'a, T>, depth: usize, landmarks: Option<usize>) -> Self {
+        TruncatedReach {
+            post: &data.post,
+            pre: &data.pre,
+            n: data.len(),
+            depth,
+            forward: RefCell::new(HashMap::new()),
+            backward: RefCell::new(HashMap::new()),
+            walks: RefCell::new(HashMap::new()),
+            landmarks: landmarks.map(|k| Landmarks::new(&data.post, &data.pre, data.len(), k)),
+        }
+    }
+
+    fn with_frontier<R>(&self, v: usize, forward: bool, f: impl FnOnce(&[(usize, usize)]) -> R) -> R {
+        let (cache, csr) = if forward { (&self.forward, self.post) } else { (&self.backward, self.pre) };
+        let mut cache = cache.borrow_mut();
+        f(cache.entry(v).or_insert_with(|| bfs(csr, v, self.depth)))
+    }
+
+    fn with_walks<R>(&self, v: usize, forward: bool, bound: Bound, f: impl FnOnce(&BitSet) -> R) -> R {
+        let csr = if forward { self.post } else { self.pre };
+        let (lo, hi) = bound.limits();
+        let hi = hi.expect("A bound with a least length has a greatest one");
+        let mut cache = self.walks.borrow_mut();
+        f(cache.entry((v, forward, bound)).or_insert_with(|| walks(csr, self.n, v, lo, hi)))
+    }
+}
+
+impl Reach for TruncatedReach<'_> {
+    fn descendants_meet(&self, v: usize, bound: Bound, a: &BitSet, b: &BitSet) -> bool {
+        let bound = match bound.limits() {
+            (0, hi) => hi.unwrap_or(usize::MAX),
+            _ => return self.with_walks(v, true, bound, |ends| intersects3(ends, a, b)),
+        };
+        if let Some(landmarks) = &self.landmarks {
+            let mut undecided = false;
+            for w in a.iter().filter(|&w| b.contains(w)) {
+                match landmarks.within(v, w, bound) {
+                    Some(true) => return true,
+                    Some(false) => {}
+                    None => undecided = true,
+                }
+            }
+            if !undecided {
+                return false;
+            }
+        }
+        self.with_frontier(v, true, |frontier| {
+            frontier.iter().take_while(|&&(_, d)| d <= bound).any(|&(w, _)| a.contains(w) && b.contains(w))
+        })
+    }
+
+    fn ancestors_in(&self, v: usize, bound: Bound, a: &BitSet) -> Vec<usize> {
+        let bound = match bound.limits() {
+            (0, hi) => hi.unwrap_or(usize::MAX),
+            _ => return self.with_walks(v, false, bound, |starts| {
+                let mut starts = starts.clone();
+                starts.intersect_with(a);
+                starts.iter().collect()
+            }),
+        };
+        if let Some(landmarks) = &self.landmarks {
+            let mut ancestors = Vec::new();
+            let mut undecided = false;
+            for w in a.iter() {
+                match landmarks.within(w, v, bound) {
+                    Some(true) => ancestors.push(w),
+                    Some(false) => {}
+                    None => {
+                        undecided = true;
+                        break;
+                    }
+                }
+            }
+            if !undecided {
+                return ancestors;
+            }
+        }
+        let mut ancestors: Vec<usize> = self.with_frontier(v, false, |frontier| {
+            frontier.iter().take_while(|&&(_, d)| d <= bound).map(|&(w, _)| w).filter(|&w| a.contains(w)).collect()
+        });
+        ancestors.sort_unstable();
+        ancestors
+    }
+}
+
+/// The nodes within `depth` steps of `source` along `csr`, `source` included, with their distance, in BFS order.
+fn bfs(csr: &Csr, source: usize, depth: usize) -> Vec<(usize, usize)> {
+    let mut visited: HashSet<usize> = HashSet::from([source]);
+    let mut frontier = vec![(source, 0)];
+    let mut queue = VecDeque::from([(source, 0)]);
+    while let Some((v, d)) = queue.pop_front() {
+        if d == depth {
+            continue;
+        }
+        for &w in csr.neighbors(v) {
+            let w = w as usize;
+            if visited.insert(w) {
+                frontier.push((w, d + 1));
+                queue.push_back((w, d + 1));
+            }
+        }
+    }
+    frontier
+}
+
+/// The exact distances from and to the `k` nodes of highest degree, which bound every other
+/// distance by the triangle inequality. `usize::MAX` stands for unreachable.
+struct Landmarks {
+    from: Vec<Vec<usize>>,
+    to: Vec<Vec<usize>>,
+}
+
+impl Landmarks {
+    fn new(post: &Csr, pre: &Csr, n: usize, k: usize) -> Self {
+        let mut nodes: Vec<usize> = (0..n).collect();
+        nodes.sort_by_key(|&v| std::cmp::Reverse(post.degree(v) + pre.degree(v)));
+        let distances = |csr: &Csr, l: usize| {
+            let mut distance = vec![usize::MAX; n];
+            for (v, d) in bfs(csr, l, usize::MAX) {
+                distance[v] = d;
+            }
+            distance
+        };
+        Landmarks {
+            from: nodes.iter().take(k).map(|&l| distances(post, l)).collect(),
+            to: nodes.iter().take(k).map(|&l| distances(pre, l)).collect(),
+        }
+    }
+
+    /// Whether the distance from `v` to `w` is at most `bound`, if the landmarks decide it.
+    fn within(&self, v: usize, w: usize, bound: usize) -> Option<bool> {
+        if v == w {
+            return Some(true);
+        }
+        for (from, to) in self.from.iter().zip(&self.to) {
+            let (from_v, from_w, to_v, to_w) = (from[v], from[w], to[v], to[w]);
+            // d(v, w) <= d(v, l) + d(l, w)
+            if to_v != usize::MAX && from_w != usize::MAX && to_v + from_w <= bound {
+                return Some(true);
+            }
+            // d(l, w) <= d(l, v) + d(v, w) and d(v, l) <= d(v, w) + d(w, l)
+            if from_v != usize::MAX && (from_w == usize::MAX || from_w.saturating_sub(from_v) > bound) {
+                return Some(false);
+            }
+            if to_w != usize::MAX && (to_v == usize::MAX || to_v.saturating_sub(to_w) > bound) {
+                return Some(false);
+            }
+        }
+        None
+    }
+}
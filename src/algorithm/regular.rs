@@ -0,0 +1,93 @@
+use graph_base::interfaces::graph::Graph;
+use graph_base::interfaces::labeled::{Label, Labeled};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::algorithm::dense::{label_candidates, refine_reach, to_relation, DenseGraph};
+use crate::utils::automaton::{EdgeRegex, RegexError};
+use crate::utils::parallel::par_map;
+
+pub trait RegularSimulation<'a> {
+    type Node: 'a;
+
+    /// The maximal regular simulation: v ∈ sim(u) iff label_same(u, v) and, for every pattern edge (u, u'),
+    /// some v' ∈ sim(u') is the end of a non-empty path from v whose edge labels match `get_regex(u, u')`.
+    /// Fails with the first expression that does not parse, see `EdgeRegex::parse`.
+    fn get_regular_simulation(&'a self, other: &'a Self) -> Result<HashMap<&'a Self::Node, HashSet<&'a Self::Node>>, RegexError>;
+}
+
+/// A pattern whose edges carry a regular expression over the edge labels of the data graph.
+pub trait Regular<'a>: Graph<'a> {
+    fn get_regex(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> &'a str;
+}
+
+impl<'a, T> RegularSimulation<'a> for T
+where
+    T: Regular<'a> + Labeled<'a>,
+    T::Node: 'a,
+{
+    type Node = T::Node;
+
+    fn get_regular_simulation(&'a self, other: &'a Self) -> Result<HashMap<&'a Self::Node, HashSet<&'a Self::Node>>, RegexError> {
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+        let index: HashMap<&T::Node, usize> = data.nodes.iter().enumerate().map(|(v, &node)| (node, v)).collect();
+        let mut post: Vec<Vec<(usize, &str)>> = vec![Vec::new(); data.len()];
+        for (v, w, label) in other.get_edges_pair_label() {
+            post[index[v]].push((index[w], label.label()));
+        }
+
+        // The pattern edges (u, u') and the automaton of each, with one automaton per distinct expression.
+        let mut regexes: HashMap<&str, usize> = HashMap::new();
+        let mut automata: Vec<EdgeRegex> = Vec::new();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut edge_automata: Vec<usize> = Vec::new();
+        for u in 0..pattern.len() {
+            for &u_prime in pattern.post.neighbors(u) {
+                let u_prime = u_prime as usize;
+                let regex = self.get_regex(pattern.nodes[u], pattern.nodes[u_prime]);
+                let r = match regexes.get(regex) {
+                    Some(&r) => r,
+                    None => {
+                        automata.push(EdgeRegex::parse(regex)?);
+                        regexes.insert(regex, automata.len() - 1);
+                        automata.len() - 1
+                    }
+                };
+                edges.push((u, u_prime));
+                edge_automata.push(r);
+            }
+        }
+
+        // As the bounds in get_bounded_simulation_matrix, but with the language of r instead of a distance:
+        // dec(r, v) := {v' | a non-empty path v/.../v' matches r}.
+        let dec: Vec<Vec<Vec<usize>>> = automata.iter().map(|automaton| {
+            par_map((0..data.len()).collect(), |v| product_reach(&post, automaton, v))
+        }).collect();
+
+        let mut sim = label_candidates(self, &pattern, &data);
+        let dec_edges: Vec<&[Vec<usize>]> = edge_automata.iter().map(|&r| dec[r].as_slice()).collect();
+        refine_reach(&edges, &dec_edges, &mut sim);
+
+        Ok(to_relation(&pattern, &data, &sim))
+    }
+}
+
+/// The ends of the non-empty paths from `v` whose labels are accepted by `automaton`,
+/// by a BFS over the product of the data graph and the automaton.
+fn product_reach(post: &[Vec<(usize, &str)>], automaton: &EdgeRegex, v: usize) -> Vec<usize> {
+    let mut visited: HashSet<(usize, usize)> = HashSet::new();
+    let mut reached = vec![false; post.len()];
+    let mut queue = VecDeque::from([(v, automaton.start())]);
+    while let Some((x, q)) = queue.pop_front() {
+        for &(y, label) in &post[x] {
+            let Some(q_prime) = automaton.step(q, label) else {
+                continue;
+            };
+            if visited.insert((y, q_prime)) {
+                reached[y] |= automaton.is_accepting(q_prime);
+                queue.push_back((y, q_prime));
+            }
+        }
+    }
+    (0..post.len()).filter(|&y| reached[y]).collect()
+}
@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// A simulation relation between the nodes of a pattern and the nodes of a data graph.
+///
+/// It owns the `HashMap<&N, HashSet<&N>>` returned by the simulation traits, which converts into
+/// it and back with `From`/`Into`. The domain of the relation is the set of keys of that map,
+/// including the pattern nodes without any match. Two relations are equal when they hold the same
+/// pairs, whatever their domains, so that results of different algorithms can be compared directly.
+pub struct SimulationRelation<'a, N> {
+    relation: HashMap<&'a N, HashSet<&'a N>>,
+}
+
+impl<'a, N: Eq + Hash> SimulationRelation<'a, N> {
+    pub fn new() -> Self {
+        SimulationRelation { relation: HashMap::new() }
+    }
+
+    /// Adds the pair `(u, v)`, returning whether it was new.
+    pub fn insert(&mut self, u: &'a N, v: &'a N) -> bool {
+        self.relation.entry(u).or_default().insert(v)
+    }
+
+    /// Removes the pair `(u, v)`, returning whether it was present. `u` stays in the domain.
+    pub fn remove(&mut self, u: &N, v: &N) -> bool {
+        self.relation.get_mut(u).is_some_and(|sim_u| sim_u.remove(v))
+    }
+
+    pub fn contains(&self, u: &N, v: &N) -> bool {
+        self.relation.get(u).is_some_and(|sim_u| sim_u.contains(v))
+    }
+
+    /// The data nodes matching `u`, i.e. sim(u).
+    pub fn matches_of(&self, u: &N) -> impl Iterator<Item = &'a N> + '_ {
+        self.relation.get(u).into_iter().flatten().copied()
+    }
+
+    /// The pattern nodes matched by `v`, i.e. {u | v ∈ sim(u)}.
+    pub fn inverse_matches_of<'b>(&'b self, v: &'b N) -> impl Iterator<Item = &'a N> + 'b {
+        self.relation.iter().filter(move |(_, sim_u)| sim_u.contains(v)).map(|(&u, _)| u)
+    }
+
+    /// The pattern nodes of the domain.
+    pub fn domain(&self) -> impl Iterator<Item = &'a N> + '_ {
+        self.relation.keys().copied()
+    }
+
+    /// Whether the domain is not empty and every node of it has a match. Unlike `Simulation::has_simulation`,
+    /// an empty domain is not total.
+    pub fn is_total(&self) -> bool {
+        !self.relation.is_empty() && self.relation.values().all(|sim_u| !sim_u.is_empty())
+    }
+
+    /// The number of pairs.
+    pub fn len(&self) -> usize {
+        self.relation.values().map(|sim_u| sim_u.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relation.values().all(|sim_u| sim_u.is_empty())
+    }
+
+    /// The pairs `(u, v)` with `v ∈ sim(u)`, in no particular order.
+    pub fn pairs(&self) -> impl Iterator<Item = (&'a N, &'a N)> + '_ {
+        self.relation.iter().flat_map(|(&u, sim_u)| sim_u.iter().map(move |&v| (u, v)))
+    }
+
+    /// The pattern nodes of the domain together with their matches.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a N, &HashSet<&'a N>)> + '_ {
+        self.relation.iter().map(|(&u, sim_u)| (u, sim_u))
+    }
+
+    /// The pairs of either relation, over both domains.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut relation = self.clone();
+        for (u, sim_u) in other.iter() {
+            relation.relation.entry(u).or_default().extend(sim_u.iter().copied());
+        }
+        relation
+    }
+
+    /// The pairs of both relations, over the domain of `self`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.filter(|u, v| other.contains(u, v))
+    }
+
+    /// The pairs of `self` that are not in `other`, over the domain of `self`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.filter(|u, v| !other.contains(u, v))
+    }
+
+    /// Whether every pair of `self` is in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.pairs().all(|(u, v)| other.contains(u, v))
+    }
+
+    fn filter(&self, keep: impl Fn(&N, &N) -> bool) -> Self {
+        let relation = self.relation.iter().map(|(&u, sim_u)| {
+            (u, sim_u.iter().copied().filter(|v| keep(u, v)).collect())
+        }).collect();
+        SimulationRelation { relation }
+    }
+}
+
+impl<'a, N: Eq + Hash> Default for SimulationRelation<'a, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, N> Clone for SimulationRelation<'a, N> {
+    fn clone(&self) -> Self {
+        SimulationRelation { relation: self.relation.clone() }
+    }
+}
+
+impl<'a, N: Eq + Hash> PartialEq for SimulationRelation<'a, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<'a, N: Eq + Hash> Eq for SimulationRelation<'a, N> {}
+
+impl<'a, N: std::fmt::Debug> std::fmt::Debug for SimulationRelation<'a, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.relation.iter()).finish()
+    }
+}
+
+/// One line `u -> {v, ...}` per node of the domain.
+impl<'a, N: std::fmt::Display> std::fmt::Display for SimulationRelation<'a, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (u, sim_u) in &self.relation {
+            let matches: Vec<String> = sim_u.iter().map(|v| v.to_string()).collect();
+            writeln!(f, "{} -> {{{}}}", u, matches.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, N> From<HashMap<&'a N, HashSet<&'a N>>> for SimulationRelation<'a, N> {
+    fn from(relation: HashMap<&'a N, HashSet<&'a N>>) -> Self {
+        SimulationRelation { relation }
+    }
+}
+
+impl<'a, N> From<SimulationRelation<'a, N>> for HashMap<&'a N, HashSet<&'a N>> {
+    fn from(relation: SimulationRelation<'a, N>) -> Self {
+        relation.relation
+    }
+}
+
+impl<'a, N: Eq + Hash> FromIterator<(&'a N, &'a N)> for SimulationRelation<'a, N> {
+    fn from_iter<I: IntoIterator<Item = (&'a N, &'a N)>>(pairs: I) -> Self {
+        let mut relation = Self::new();
+        for (u, v) in pairs {
+            relation.insert(u, v);
+        }
+        relation
+    }
+}
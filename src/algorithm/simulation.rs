@@ -3,6 +3,11 @@ use graph_base::interfaces::labeled::{Labeled, LabeledAdjacency};
 
 use std::cell::RefCell;
 use std::collections::{HashSet, HashMap};
+
+use crate::algorithm::explain::{Reason, SimulationExplanation};
+use crate::algorithm::relation::SimulationRelation;
+use crate::algorithm::dense::{label_candidates, refine_counting, refine_rounds, to_relation, DenseGraph};
+use crate::utils::parallel::{par_filter, MaybeSync};
 pub trait Simulation<'a> {
     type Node: 'a;
 
@@ -10,120 +15,135 @@ pub trait Simulation<'a> {
 
     fn get_simulation_inter(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
 
+    /// `get_simulation_inter`, recording for every pair that is not in the result why it was removed.
+    fn get_simulation_inter_explained(&'a self, other: &'a Self) -> SimulationExplanation<'a, Self::Node>;
+
+    fn get_simulation_hhk(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
+    /// `get_simulation_inter` on dense indices: bitset sim sets, CSR adjacency and counters instead of hashed node references.
+    fn get_simulation_inter_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
+    fn get_dual_simulation(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
     fn get_simulation_native(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
 
+    /// `get_simulation_native` on dense indices.
+    fn get_simulation_native_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
     fn get_simulation_of_node_edge(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
 
+    /// `get_simulation_of_node_edge` on dense indices.
+    fn get_simulation_of_node_edge_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
+
     fn get_simulation_of_edge(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>>;
     
-    fn has_simulation(sim: HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> bool;
+    fn has_simulation(sim: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> bool;
+
+    /// `get_simulation_hhk` as a `SimulationRelation`.
+    fn get_simulation_relation(&'a self, other: &'a Self) -> SimulationRelation<'a, Self::Node> {
+        self.get_simulation_hhk(other).into()
+    }
+
+    /// `get_dual_simulation` as a `SimulationRelation`.
+    fn get_dual_simulation_relation(&'a self, other: &'a Self) -> SimulationRelation<'a, Self::Node> {
+        self.get_dual_simulation(other).into()
+    }
 }
 
 impl<'a, 'b, T> Simulation<'a> for T
 where 
-    T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> + Labeled<'a> + Directed + LabeledAdjacency<'a>,
-    T::Node: 'a, T::Edge: 'a,
+    T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> + Labeled<'a> + Directed + LabeledAdjacency<'a> + MaybeSync,
+    T::Node: 'a + MaybeSync, T::Edge: 'a,
     'b: 'a
 {
     type Node = T::Node;
 
     fn get_simulation(&'a self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        // The maximal simulation of the graph on itself: w ∈ sim(u) iff w simulates u.
+        self.get_simulation_hhk(self)
+    }
+
+    fn get_simulation_inter(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        simulation_inter(self, other, None)
+    }
+
+    fn get_simulation_inter_explained(&'a self, other: &'a Self) -> SimulationExplanation<'a, Self::Node> {
+        let mut explanation = SimulationExplanation::new();
+        explanation.relation = simulation_inter(self, other, Some(&mut explanation));
+        explanation
+    }
+
+    fn get_simulation_hhk(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
         let mut simulation: HashMap<&'a <T as Graph<'_>>::Node, HashSet<&'a <T as Graph<'_>>::Node>> = HashMap::new();
-        let remove = RefCell::new(HashMap::new());
+        let mut remove: HashMap<&'a T::Node, HashSet<&'a T::Node>> = HashMap::new();
+        // count[(v, w)] = |post(w) ∩ sim(v)|, so that w joins remove(v) exactly when its last child leaves sim(v).
+        let mut count: HashMap<(&'a T::Node, &'a T::Node), usize> = HashMap::new();
         let (adj, adj_inv) = (self.get_adj(), self.get_adj_inv());
+        let (adj_other, adj_inv_other) = (other.get_adj(), other.get_adj_inv());
 
-        let pre_V = self.nodes().map(|v| self.get_post(&adj, v).collect::<HashSet<_>>()).reduce(|acc, x| acc.union(&x).cloned().collect()).unwrap();
+        let pre_v: HashSet<_> = other.nodes().filter(|w| other.get_post(&adj_other, w).count() != 0).collect();
 
         for v in self.nodes() {
-            let sim_v: HashSet<_> = if self.get_post(&adj, v).count() != 0 {
-                self.nodes().filter(|u| self.label_same(v, u)).collect()
+            let sim_v: HashSet<_> = if self.get_post(&adj, v).count() == 0 {
+                par_filter(other.nodes(), |u| self.label_same(v, u))
             } else {
-                self.nodes().filter(|u| self.label_same(v, u) && self.get_post(&adj,u).count() != 0).collect()
+                par_filter(other.nodes(), |u| self.label_same(v, u) && other.get_post(&adj_other, u).count() != 0)
             };
-            simulation.insert(v, sim_v.clone());
 
-            let pre_sim_v = sim_v.into_iter().map(|u| self.get_pre(&adj_inv, u).collect::<HashSet<_>>()).reduce(|acc, x| acc.union(&x).cloned().collect()).unwrap();
-            let res: HashSet<_> = pre_V.clone().into_iter().filter(|u| !pre_sim_v.contains(u)).collect();
-            remove.borrow_mut().insert(v, res);
-        }
-
-        let legal_v = || {
-            for v in self.nodes() {
-                if remove.borrow().get(v).unwrap().len() != 0 {
-                    return Some(v);
+            for u in &sim_v {
+                for w in other.get_pre(&adj_inv_other, u) {
+                    *count.entry((v, w)).or_default() += 1;
                 }
             }
-            None
-        };
+            remove.insert(v, pre_v.iter().copied().filter(|w| !count.contains_key(&(v, *w))).collect());
+            simulation.insert(v, sim_v);
+        }
+
+        let mut worklist: Vec<&'a T::Node> = self.nodes().filter(|v| !remove.get(v).unwrap().is_empty()).collect();
 
-        while let Some(v) = legal_v() {
-            for u in self.get_pre(&adj_inv,v) {
-                for w in remove.borrow().get(v).unwrap() {
-                    if simulation.get(u).unwrap().contains(w) {
-                        simulation.get_mut(u).unwrap().remove(w);
-                        for w_prime in self.get_pre(&adj_inv, w) {
-                            if self.get_post(&adj, w_prime).collect::<HashSet<_>>().intersection(simulation.get(u).unwrap()).count() == 0 {
-                                remove.borrow_mut().get_mut(u).unwrap().insert(w_prime);
+        while let Some(v) = worklist.pop() {
+            let remove_v = std::mem::take(remove.get_mut(v).unwrap());
+            for u in self.get_pre(&adj_inv, v) {
+                for &w in &remove_v {
+                    if !simulation.get_mut(u).unwrap().remove(w) {
+                        continue;
+                    }
+                    for w_prime in other.get_pre(&adj_inv_other, w) {
+                        let count_u = count.get_mut(&(u, w_prime)).unwrap();
+                        *count_u -= 1;
+                        if *count_u == 0 {
+                            let remove_u = remove.get_mut(u).unwrap();
+                            if remove_u.is_empty() {
+                                worklist.push(u);
                             }
+                            remove_u.insert(w_prime);
                         }
                     }
-
                 }
             }
-            remove.borrow_mut().get_mut(v).unwrap().clear();
         }
-
         simulation
     }
 
-    fn get_simulation_inter(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
-        let mut simulation: HashMap<&'a <T as Graph<'_>>::Node, HashSet<&'a <T as Graph<'_>>::Node>> = HashMap::new();
-        let remove = RefCell::new(HashMap::new());
-        let (adj, adj_inv) = (self.get_adj(), self.get_adj_inv());
-        let (adj_other, adj_inv_other) = (other.get_adj(), other.get_adj_inv());
-
-        let pre_V = other.nodes().map(|v| other.get_pre(&adj_inv_other, v).collect::<HashSet<_>>()).reduce(|acc, x| acc.union(&x).cloned().collect()).unwrap();
-        
-        for v in self.nodes() {
-            let sim_v: HashSet<_> = if self.get_post(&adj, v).count() == 0 {
-                other.nodes().filter(|u| self.label_same(v, u)).collect()
-            } else {
-                other.nodes().filter(|u| self.label_same(v, u) && other.get_post(&adj_other,u).count() != 0).collect()
-            };
-            simulation.insert(v, sim_v.clone());
-            
-            let pre_sim_v = sim_v.clone().iter().map(|u| other.get_pre(&adj_inv_other, u).collect::<HashSet<_>>()).reduce(|acc, x| acc.union(&x).cloned().collect()).unwrap_or(HashSet::new());
-            let res: HashSet<_> = pre_V.difference(&pre_sim_v).copied().collect();
-            remove.borrow_mut().insert(v, res);
-        }   
-
-        let legal_v = || {
-            for v in self.nodes() {
-                if remove.borrow().get(v).unwrap().len() != 0 {
-                    return Some(v);
+    fn get_simulation_inter_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+        let mut sim = label_candidates(self, &pattern, &data);
+        for (u, sim_u) in sim.iter_mut().enumerate() {
+            if pattern.post.degree(u) != 0 {
+                for v in (0..data.len()).filter(|&v| data.post.degree(v) == 0) {
+                    sim_u.remove(v);
                 }
             }
-            None
-        };
-
-        while let Some(v) = legal_v() {
-            for u in self.get_pre(&adj_inv,v) {
-                let mut remove_u_add = HashSet::new();
-                for w in remove.borrow().get(v).unwrap() {
-                    if simulation.get(u).unwrap().contains(w) {
-                        simulation.get_mut(u).unwrap().remove(w);
-                        for w_prime in other.get_pre(&adj_inv_other, w) {
-                            if other.get_post(&adj_other, w_prime).collect::<HashSet<_>>().intersection(simulation.get(u).unwrap()).count() == 0 {
-                                remove_u_add.insert(w_prime);
-                            }
-                        }
-                    }
-                }
-                remove.borrow_mut().get_mut(u).unwrap().extend(remove_u_add);
-            }
-            remove.borrow_mut().get_mut(v).unwrap().clear();
         }
-        simulation
+        refine_counting(&pattern, &data, &mut sim);
+        to_relation(&pattern, &data, &sim)
+    }
+
+    fn get_dual_simulation(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        let simulation = self.nodes().map(|v| {
+            (v, par_filter(other.nodes(), |u| self.label_same(v, u)))
+        }).collect();
+        dual_simulation_within(self, other, simulation, |_| true)
     }
 
     fn get_simulation_native(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
@@ -131,7 +151,7 @@ where
         let (adj_other, _) = (other.get_adj(), other.get_adj_inv());
         
         for v in self.nodes() {
-            let sim_v: HashSet<_> = other.nodes().filter(|u| self.label_same(v, u)).collect();
+            let sim_v: HashSet<_> = par_filter(other.nodes(), |u| self.label_same(v, u));
             simulation.insert(v, sim_v.clone());
         }
 
@@ -139,20 +159,11 @@ where
         while changed {
             changed = false;
             for (u, u_prime) in self.get_edges_pair() {
-                let mut sim_u_remove = HashSet::new();
-                for v in simulation.get(u).unwrap() {
-                    let mut v_need_remove = true;
-                    for v_prime in other.get_post(&adj_other, v) {
-                        if simulation.get(u_prime).unwrap().contains(v_prime) {
-                            v_need_remove = false;
-                            break;
-                        }
-                    }
-                    if v_need_remove {
-                        sim_u_remove.insert(v.clone());
-                        changed = true;
-                    }
-                }
+                let sim_u_prime = simulation.get(u_prime).unwrap();
+                let sim_u_remove = par_filter(simulation.get(u).unwrap().iter().copied(), |v| {
+                    !other.get_post(&adj_other, v).any(|v_prime| sim_u_prime.contains(v_prime))
+                });
+                changed |= !sim_u_remove.is_empty();
                 for v in sim_u_remove {
                     simulation.get_mut(u).unwrap().remove(v);
                 }
@@ -163,12 +174,19 @@ where
         simulation
     }
 
+    fn get_simulation_native_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+        let mut sim = label_candidates(self, &pattern, &data);
+        refine_rounds(&pattern, &data, &mut sim, |_, _| true);
+        to_relation(&pattern, &data, &sim)
+    }
+
     fn get_simulation_of_node_edge(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
         let mut simulation: HashMap<&'a <T as Graph<'_>>::Node, HashSet<&'a <T as Graph<'_>>::Node>> = HashMap::new();
         let (adj_other, _) = (other.get_labeled_adj(), other.get_adj_inv());
         
         for v in self.nodes() {
-            let sim_v: HashSet<_> = other.nodes().filter(|u| self.label_same(v, u)).collect();
+            let sim_v: HashSet<_> = par_filter(other.nodes(), |u| self.label_same(v, u));
             simulation.insert(v, sim_v.clone());
         }
 
@@ -200,6 +218,15 @@ where
         simulation
     }
 
+    fn get_simulation_of_node_edge_dense(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+        let mut sim = label_candidates(self, &pattern, &data);
+        refine_rounds(&pattern, &data, &mut sim, |u_edge, v_edge| {
+            self.edge_label_same(pattern.edges[u_edge], data.edges[v_edge])
+        });
+        to_relation(&pattern, &data, &sim)
+    }
+
     fn get_simulation_of_edge(&'a self, other: &'a Self) -> HashMap<&'a Self::Node, HashSet<&'a Self::Node>> {
         let mut simulation: HashMap<&'a <T as Graph<'_>>::Node, HashSet<&'a <T as Graph<'_>>::Node>> = HashMap::new();
         let (adj_other, _) = (other.get_labeled_adj(), other.get_adj_inv());
@@ -237,13 +264,181 @@ where
         simulation
     }
 
-    fn has_simulation(sim: HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> bool {
+    fn has_simulation(sim: &HashMap<&'a Self::Node, HashSet<&'a Self::Node>>) -> bool {
         sim.iter().all(|(_, sim_v)| {
             sim_v.len() != 0
         })
     }
 }
 
+/// The refinement of `get_simulation_inter`. With an `explanation`, every pair left out of the
+/// initial candidates or removed later is recorded in it, with the pattern edge and the removed
+/// pair that caused the removal.
+fn simulation_inter<'a, T>(
+    graph: &'a T,
+    other: &'a T,
+    mut explanation: Option<&mut SimulationExplanation<'a, T::Node>>,
+) -> HashMap<&'a T::Node, HashSet<&'a T::Node>>
+where T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> + Labeled<'a> + MaybeSync, T::Node: MaybeSync {
+    let mut simulation: HashMap<&'a T::Node, HashSet<&'a T::Node>> = HashMap::new();
+    let mut remove: HashMap<&'a T::Node, HashSet<&'a T::Node>> = HashMap::new();
+    // cause[(v, w)] is a child of w that is not in sim(v), recorded when w joins remove(v).
+    let mut cause: HashMap<(&'a T::Node, &'a T::Node), &'a T::Node> = HashMap::new();
+    let (adj, adj_inv) = (graph.get_adj(), graph.get_adj_inv());
+    let (adj_other, adj_inv_other) = (other.get_adj(), other.get_adj_inv());
+
+    let pre_v: HashSet<_> = other.nodes().filter(|w| other.get_post(&adj_other, w).count() != 0).collect();
+
+    for v in graph.nodes() {
+        let has_post = graph.get_post(&adj, v).count() != 0;
+        let sim_v: HashSet<_> = if !has_post {
+            par_filter(other.nodes(), |u| graph.label_same(v, u))
+        } else {
+            par_filter(other.nodes(), |u| graph.label_same(v, u) && other.get_post(&adj_other, u).count() != 0)
+        };
+        if let Some(explanation) = explanation.as_deref_mut() {
+            for u in other.nodes().filter(|u| !sim_v.contains(u)) {
+                let reason = if graph.label_same(v, u) { Reason::DeadEnd } else { Reason::LabelMismatch };
+                explanation.record(v, u, reason, 0, 0);
+            }
+        }
+
+        let pre_sim_v: HashSet<_> = sim_v.iter().flat_map(|u| other.get_pre(&adj_inv_other, u)).collect();
+        let remove_v: HashSet<_> = pre_v.difference(&pre_sim_v).copied().collect();
+        for &w in &remove_v {
+            cause.insert((v, w), other.get_post(&adj_other, w).next().unwrap());
+        }
+        remove.insert(v, remove_v);
+        simulation.insert(v, sim_v);
+    }
+
+    let (mut round, mut step) = (0, 0);
+    while let Some(v) = graph.nodes().find(|v| !remove.get(v).unwrap().is_empty()) {
+        round += 1;
+        // Taken before the parents are processed: with a self-loop, v is one of them and refills remove(v).
+        let remove_v: Vec<_> = std::mem::take(remove.get_mut(v).unwrap()).into_iter().collect();
+        for u in graph.get_pre(&adj_inv, v) {
+            let mut remove_u_add = HashSet::new();
+            for &w in &remove_v {
+                if !simulation.get_mut(u).unwrap().remove(w) {
+                    continue;
+                }
+                step += 1;
+                if let Some(explanation) = explanation.as_deref_mut() {
+                    let reason = Reason::Unmatched { child: v, bound: None, cause: Some((v, cause[&(v, w)])) };
+                    explanation.record(u, w, reason, round, step);
+                }
+                for w_prime in other.get_pre(&adj_inv_other, w) {
+                    if !other.get_post(&adj_other, w_prime).any(|x| simulation.get(u).unwrap().contains(x)) {
+                        remove_u_add.insert(w_prime);
+                        cause.insert((u, w_prime), w);
+                    }
+                }
+            }
+            remove.get_mut(u).unwrap().extend(remove_u_add);
+        }
+    }
+    simulation
+}
+
+/// Refines `simulation` to the maximal dual simulation of `graph` in the subgraph of `other`
+/// induced by the nodes accepted by `within`.
+///
+/// `simulation` holds the initial candidates of every node of `graph`; candidates outside of
+/// `within` are dropped. The refinement keeps, for each pattern node `v`, the sets
+/// remove(v) (nodes with a child but no child in sim(v)) and remove_inv(v) (nodes with a parent
+/// but no parent in sim(v)), in the same way as `get_simulation_inter`.
+pub(crate) fn dual_simulation_within<'a, T>(
+    graph: &'a T,
+    other: &'a T,
+    mut simulation: HashMap<&'a T::Node, HashSet<&'a T::Node>>,
+    within: impl Fn(&T::Node) -> bool,
+) -> HashMap<&'a T::Node, HashSet<&'a T::Node>>
+where T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> {
+    let remove = RefCell::new(HashMap::new());
+    let remove_inv = RefCell::new(HashMap::new());
+    let (adj, adj_inv) = adjacency(graph);
+    let (adj_other, adj_inv_other) = adjacency(other);
+    let within = &within;
+    let post = |v: &'a T::Node| adj[v].iter().copied();
+    let pre = |v: &'a T::Node| adj_inv[v].iter().copied();
+    let post_other = |w: &'a T::Node| adj_other[w].iter().copied().filter(move |x| within(x));
+    let pre_other = |w: &'a T::Node| adj_inv_other[w].iter().copied().filter(move |x| within(x));
+
+    let pre_v: HashSet<_> = other.nodes().filter(|w| within(w) && post_other(w).count() != 0).collect();
+    let post_v: HashSet<_> = other.nodes().filter(|w| within(w) && pre_other(w).count() != 0).collect();
+
+    for v in graph.nodes() {
+        let has_post = post(v).count() != 0;
+        let has_pre = pre(v).count() != 0;
+        let sim_v = simulation.entry(v).or_default();
+        sim_v.retain(|u| {
+            within(u)
+                && (!has_post || post_other(u).count() != 0)
+                && (!has_pre || pre_other(u).count() != 0)
+        });
+
+        let pre_sim_v: HashSet<_> = sim_v.iter().flat_map(|u| pre_other(u)).collect();
+        let post_sim_v: HashSet<_> = sim_v.iter().flat_map(|u| post_other(u)).collect();
+        remove.borrow_mut().insert(v, pre_v.difference(&pre_sim_v).copied().collect::<HashSet<_>>());
+        remove_inv.borrow_mut().insert(v, post_v.difference(&post_sim_v).copied().collect::<HashSet<_>>());
+    }
+
+    let legal_v = || graph.nodes().find(|v| {
+        !remove.borrow().get(v).unwrap().is_empty() || !remove_inv.borrow().get(v).unwrap().is_empty()
+    });
+
+    // Removing w from sim(u) may leave parents of w without a child in sim(u),
+    // and children of w without a parent in sim(u).
+    let remove_from_sim = |simulation: &mut HashMap<&'a T::Node, HashSet<&'a T::Node>>, u: &'a T::Node, w: &'a T::Node| {
+        if !simulation.get_mut(u).unwrap().remove(w) {
+            return;
+        }
+        let sim_u = simulation.get(u).unwrap();
+        for w_prime in pre_other(w) {
+            if !post_other(w_prime).any(|x| sim_u.contains(x)) {
+                remove.borrow_mut().get_mut(u).unwrap().insert(w_prime);
+            }
+        }
+        for w_prime in post_other(w) {
+            if !pre_other(w_prime).any(|x| sim_u.contains(x)) {
+                remove_inv.borrow_mut().get_mut(u).unwrap().insert(w_prime);
+            }
+        }
+    };
+
+    while let Some(v) = legal_v() {
+        let remove_v = std::mem::take(remove.borrow_mut().get_mut(v).unwrap());
+        for u in pre(v) {
+            for &w in &remove_v {
+                remove_from_sim(&mut simulation, u, w);
+            }
+        }
+
+        let remove_inv_v = std::mem::take(remove_inv.borrow_mut().get_mut(v).unwrap());
+        for u in post(v) {
+            for &w in &remove_inv_v {
+                remove_from_sim(&mut simulation, u, w);
+            }
+        }
+    }
+    simulation
+}
+
+type AdjacencyMap<'a, N> = HashMap<&'a N, Vec<&'a N>>;
+
+/// The successors and the predecessors of every node of `graph`. graph-base's `get_post` and `get_pre` format the whole
+/// adjacency list on every call, which dominates the loops that call them per node.
+pub(crate) fn adjacency<'a, T: Graph<'a>>(graph: &'a T) -> (AdjacencyMap<'a, T::Node>, AdjacencyMap<'a, T::Node>) {
+    let mut post: AdjacencyMap<'a, T::Node> = graph.nodes().map(|v| (v, Vec::new())).collect();
+    let mut pre: AdjacencyMap<'a, T::Node> = graph.nodes().map(|v| (v, Vec::new())).collect();
+    for (v, v_prime) in graph.get_edges_pair() {
+        post.get_mut(v).unwrap().push(v_prime);
+        pre.get_mut(v_prime).unwrap().push(v);
+    }
+    (post, pre)
+}
+
 pub trait HyperSimulation<'a> {
     type Node: 'a;
 
@@ -0,0 +1,149 @@
+use graph_base::interfaces::graph::{Adjacency, AdjacencyInv, AdjacencyList, Directed, Graph};
+use graph_base::interfaces::labeled::Labeled;
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::algorithm::simulation::dual_simulation_within;
+
+/// A match of strong simulation: the connected part of the match graph around `center`,
+/// together with the dual simulation relation restricted to it.
+pub struct MatchGraph<'a, N> {
+    center: &'a N,
+    relation: HashMap<&'a N, HashSet<&'a N>>,
+    edges: HashSet<(&'a N, &'a N)>,
+}
+
+impl<'a, N: Eq + std::hash::Hash> MatchGraph<'a, N> {
+    pub fn center(&self) -> &'a N {
+        self.center
+    }
+
+    /// The pattern nodes mapped to the data nodes of this match.
+    pub fn relation(&self) -> &HashMap<&'a N, HashSet<&'a N>> {
+        &self.relation
+    }
+
+    pub fn nodes(&self) -> HashSet<&'a N> {
+        self.relation.values().flatten().copied().collect()
+    }
+
+    /// The data edges `(v, v')` matching some pattern edge `(u, u')` with `v ∈ sim(u)` and `v' ∈ sim(u')`.
+    pub fn edges(&self) -> &HashSet<(&'a N, &'a N)> {
+        &self.edges
+    }
+}
+
+pub trait StrongSimulation<'a> {
+    type Node: 'a;
+
+    /// The largest shortest undirected distance between two connected nodes.
+    fn get_diameter(&'a self) -> usize;
+
+    /// Strong simulation (Ma et al.): every ball of radius `self.get_diameter()` around a node of
+    /// `other` is matched by dual simulation, and the connected part of the match graph containing
+    /// the center of the ball is reported. The pattern `self` is assumed to be connected.
+    fn get_strong_simulation(&'a self, other: &'a Self) -> Vec<MatchGraph<'a, Self::Node>>;
+}
+
+impl<'a, T> StrongSimulation<'a> for T
+where
+    T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> + Labeled<'a> + Directed,
+    T::Node: 'a, T::Edge: 'a,
+{
+    type Node = T::Node;
+
+    fn get_diameter(&'a self) -> usize {
+        let (adj, adj_inv) = (self.get_adj(), self.get_adj_inv());
+        self.nodes().map(|u| {
+            get_ball(self, &adj, &adj_inv, u, usize::MAX).into_values().max().unwrap_or(0)
+        }).max().unwrap_or(0)
+    }
+
+    fn get_strong_simulation(&'a self, other: &'a Self) -> Vec<MatchGraph<'a, Self::Node>> {
+        let diameter = self.get_diameter();
+        let (adj_other, adj_inv_other) = (other.get_adj(), other.get_adj_inv());
+
+        // The dual simulation inside a ball is contained in the dual simulation over the whole of `other`,
+        // so the latter is computed once and used as the initial candidates of every ball.
+        let initial = self.nodes().map(|u| {
+            (u, other.nodes().filter(|v| self.label_same(u, v)).collect())
+        }).collect();
+        let global = dual_simulation_within(self, other, initial, |_| true);
+        if global.values().any(|sim_u| sim_u.is_empty()) {
+            return Vec::new();
+        }
+        let matched: HashSet<&T::Node> = global.values().flatten().copied().collect();
+
+        let mut matches: Vec<MatchGraph<'a, T::Node>> = Vec::new();
+        for w in other.nodes().filter(|w| matched.contains(w)) {
+            let ball = get_ball(other, &adj_other, &adj_inv_other, w, diameter);
+            let initial = global.iter().map(|(&u, sim_u)| {
+                (u, sim_u.iter().copied().filter(|v| ball.contains_key(v)).collect())
+            }).collect();
+            let sim = dual_simulation_within(self, other, initial, |v| ball.contains_key(v));
+            if sim.values().any(|sim_u| sim_u.is_empty()) || !sim.values().any(|sim_u| sim_u.contains(w)) {
+                continue;
+            }
+
+            // The match graph of the ball, and its connected component containing the center.
+            let mut edges: HashSet<(&T::Node, &T::Node)> = HashSet::new();
+            for (u, u_prime) in self.get_edges_pair() {
+                for &v in sim.get(u).unwrap() {
+                    for v_prime in other.get_post(&adj_other, v) {
+                        if sim.get(u_prime).unwrap().contains(v_prime) {
+                            edges.insert((v, v_prime));
+                        }
+                    }
+                }
+            }
+            let mut neighbors: HashMap<&T::Node, Vec<&T::Node>> = HashMap::new();
+            for &(v, v_prime) in &edges {
+                neighbors.entry(v).or_default().push(v_prime);
+                neighbors.entry(v_prime).or_default().push(v);
+            }
+            let mut component: HashSet<&T::Node> = HashSet::from([w]);
+            let mut queue = VecDeque::from([w]);
+            while let Some(v) = queue.pop_front() {
+                for &v_prime in neighbors.get(v).into_iter().flatten() {
+                    if component.insert(v_prime) {
+                        queue.push_back(v_prime);
+                    }
+                }
+            }
+
+            let relation: HashMap<_, HashSet<_>> = sim.into_iter().map(|(u, sim_u)| {
+                (u, sim_u.into_iter().filter(|v| component.contains(v)).collect())
+            }).collect();
+            edges.retain(|(v, _)| component.contains(v));
+
+            // Balls around different centers may yield the same match.
+            if matches.iter().any(|m| m.relation == relation) {
+                continue;
+            }
+            matches.push(MatchGraph { center: w, relation, edges });
+        }
+
+        matches
+    }
+}
+
+/// Undirected BFS from `center`, returning the distance of every node within `radius`.
+fn get_ball<'a, T>(graph: &'a T, adj: &AdjacencyList<'a, T>, adj_inv: &AdjacencyList<'a, T>, center: &'a T::Node, radius: usize) -> HashMap<&'a T::Node, usize>
+where T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> {
+    let mut distance = HashMap::from([(center, 0)]);
+    let mut queue = VecDeque::from([center]);
+    while let Some(v) = queue.pop_front() {
+        let dist = distance[v];
+        if dist == radius {
+            continue;
+        }
+        for v_prime in graph.get_post(adj, v).chain(graph.get_pre(adj_inv, v)) {
+            if let Entry::Vacant(entry) = distance.entry(v_prime) {
+                entry.insert(dist + 1);
+                queue.push_back(v_prime);
+            }
+        }
+    }
+    distance
+}
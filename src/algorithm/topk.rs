@@ -0,0 +1,168 @@
+use graph_base::interfaces::graph::{Adjacency, AdjacencyInv, Directed, Graph};
+use graph_base::interfaces::labeled::{Labeled, LabeledAdjacency};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::algorithm::dense::{distinct_neighbors, DenseGraph};
+use crate::algorithm::simulation::Simulation;
+use crate::utils::parallel::MaybeSync;
+
+pub trait TopKSimulation<'a>: Simulation<'a> {
+    /// The `k` best nodes of sim(u) of `get_simulation_inter` for every pattern node u, best first, where a greater
+    /// `rank(u, v)` is better and ties keep the order of `other.nodes()`. A float score can be ranked as a `Score`.
+    ///
+    /// The candidates of u are verified in the order of their rank, each by the greatest fixpoint over the pairs it
+    /// depends on only, and the verification of u stops at its k-th match: the full relation is not computed.
+    fn get_simulation_top_k<R: Ord>(
+        &'a self,
+        other: &'a Self,
+        k: usize,
+        rank: impl Fn(&'a Self::Node, &'a Self::Node) -> R,
+    ) -> HashMap<&'a Self::Node, Vec<&'a Self::Node>>;
+}
+
+/// An `f64` rank, totally ordered by `f64::total_cmp`: a NaN is not equal to every score but above or below all of them.
+#[derive(Debug, Clone, Copy)]
+pub struct Score(pub f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<'a, T> TopKSimulation<'a> for T
+where
+    T: Graph<'a> + Adjacency<'a> + AdjacencyInv<'a> + Labeled<'a> + Directed + LabeledAdjacency<'a> + MaybeSync,
+    T::Node: 'a + MaybeSync,
+    T::Edge: 'a,
+{
+    fn get_simulation_top_k<R: Ord>(
+        &'a self,
+        other: &'a Self,
+        k: usize,
+        rank: impl Fn(&'a T::Node, &'a T::Node) -> R,
+    ) -> HashMap<&'a T::Node, Vec<&'a T::Node>> {
+        let (pattern, data) = (DenseGraph::new(self), DenseGraph::new(other));
+        let mut verifier = Verifier {
+            pattern_post: distinct_neighbors(&pattern.post),
+            data_post: distinct_neighbors(&data.post),
+            label_same: |u: usize, v: usize| self.label_same(pattern.nodes[u], data.nodes[v]),
+            decided: HashMap::new(),
+        };
+
+        let mut top_k = HashMap::new();
+        for (u, &u_node) in pattern.nodes.iter().enumerate() {
+            let mut candidates: Vec<(R, usize)> = (0..data.len())
+                .filter(|&v| (verifier.label_same)(u, v))
+                .map(|v| (rank(u_node, data.nodes[v]), v))
+                .collect();
+            candidates.sort_by(|(a, _), (b, _)| b.cmp(a));
+            let matches = candidates.into_iter()
+                .filter(|&(_, v)| verifier.verify(u, v))
+                .take(k)
+                .map(|(_, v)| data.nodes[v])
+                .collect();
+            top_k.insert(u_node, matches);
+        }
+        top_k
+    }
+}
+
+/// Decides single pairs of the maximal simulation on demand, remembering every pair decided on the way.
+struct Verifier<F: Fn(usize, usize) -> bool> {
+    pattern_post: Vec<Vec<usize>>,
+    data_post: Vec<Vec<usize>>,
+    label_same: F,
+    decided: HashMap<(usize, usize), bool>,
+}
+
+impl<F: Fn(usize, usize) -> bool> Verifier<F> {
+    /// Whether v ∈ sim(u), for a pair with the same label.
+    ///
+    /// Whether a pair is in the maximal simulation only depends on the pairs (u', v') of its children, so the
+    /// greatest fixpoint over the undecided pairs reachable this way is exact for all of them.
+    fn verify(&mut self, u: usize, v: usize) -> bool {
+        if let Some(&kept) = self.decided.get(&(u, v)) {
+            return kept;
+        }
+
+        // The undecided pairs that (u, v) depends on, and for each its children pairs grouped by pattern child.
+        let mut index: HashMap<(usize, usize), usize> = HashMap::from([((u, v), 0)]);
+        let mut pairs: Vec<(usize, usize)> = vec![(u, v)];
+        let mut children: Vec<Vec<Vec<usize>>> = Vec::new();
+        // count[i][c]: the children of pair i for its c-th pattern child that are still kept, decided pairs included.
+        let mut count: Vec<Vec<usize>> = Vec::new();
+        let mut i = 0;
+        while i < pairs.len() {
+            let (x, y) = pairs[i];
+            let (mut children_i, mut count_i) = (Vec::new(), Vec::new());
+            for &x_prime in &self.pattern_post[x] {
+                let (mut undecided, mut kept) = (Vec::new(), 0);
+                for &y_prime in self.data_post[y].iter().filter(|&&y_prime| (self.label_same)(x_prime, y_prime)) {
+                    match self.decided.get(&(x_prime, y_prime)) {
+                        Some(false) => continue,
+                        Some(true) => {}
+                        None => {
+                            let j = *index.entry((x_prime, y_prime)).or_insert_with(|| {
+                                pairs.push((x_prime, y_prime));
+                                pairs.len() - 1
+                            });
+                            undecided.push(j);
+                        }
+                    }
+                    kept += 1;
+                }
+                children_i.push(undecided);
+                count_i.push(kept);
+            }
+            children.push(children_i);
+            count.push(count_i);
+            i += 1;
+        }
+
+        // parents[j]: the (pair, pattern child) counters that pair j is counted in.
+        let mut parents: Vec<Vec<(usize, usize)>> = vec![Vec::new(); pairs.len()];
+        for (i, children_i) in children.iter().enumerate() {
+            for (c, children_c) in children_i.iter().enumerate() {
+                for &j in children_c {
+                    parents[j].push((i, c));
+                }
+            }
+        }
+        let mut kept = vec![true; pairs.len()];
+        let mut removed: Vec<usize> = (0..pairs.len()).filter(|&i| count[i].contains(&0)).collect();
+        for &i in &removed {
+            kept[i] = false;
+        }
+        while let Some(j) = removed.pop() {
+            for &(i, c) in &parents[j] {
+                count[i][c] -= 1;
+                if kept[i] && count[i][c] == 0 {
+                    kept[i] = false;
+                    removed.push(i);
+                }
+            }
+        }
+
+        for (pair, kept) in pairs.into_iter().zip(kept) {
+            self.decided.insert(pair, kept);
+        }
+        self.decided[&(u, v)]
+    }
+}
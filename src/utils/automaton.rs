@@ -0,0 +1,339 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+
+/// A regular expression over edge labels, compiled to a deterministic automaton.
+///
+/// The syntax is:
+/// - a label: a run of characters other than whitespace and `()|*+?{},.·`, e.g. `friend`;
+/// - `_`: any single label;
+/// - `r·s`, `r.s` or `r s`: concatenation;
+/// - `r|s`: alternation;
+/// - `r*`, `r+`, `r?`, `r{m}`, `r{m,}` and `r{m,n}`: repetition;
+/// - `(r)`: grouping.
+///
+/// For example `friend{1,3}·colleague` matches one to three `friend` edges followed by a `colleague` edge.
+#[derive(Debug, Clone)]
+pub struct EdgeRegex {
+    // transitions[q] maps a label to the next state, and wildcard[q] is the next state for any other label.
+    transitions: Vec<HashMap<String, usize>>,
+    wildcard: Vec<Option<usize>>,
+    accepting: Vec<bool>,
+}
+
+/// The error of `EdgeRegex::parse`: what went wrong and at which character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at character {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+impl EdgeRegex {
+    pub fn parse(regex: &str) -> Result<Self, RegexError> {
+        let mut parser = Parser { chars: regex.chars().collect(), position: 0 };
+        let ast = parser.alternation()?;
+        parser.skip_whitespace();
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        let mut nfa = Nfa::default();
+        let (start, end) = nfa.compile(&ast);
+        Ok(nfa.determinize(start, end))
+    }
+
+    /// The initial state.
+    pub fn start(&self) -> usize {
+        0
+    }
+
+    /// The state after reading `label` in `state`, if any.
+    pub fn step(&self, state: usize, label: &str) -> Option<usize> {
+        self.transitions[state].get(label).copied().or(self.wildcard[state])
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    /// The number of states.
+    pub fn len(&self) -> usize {
+        self.accepting.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accepting.is_empty()
+    }
+
+    /// Whether the sequence of labels is in the language of the expression.
+    pub fn matches<'s>(&self, labels: impl IntoIterator<Item = &'s str>) -> bool {
+        let mut state = self.start();
+        for label in labels {
+            match self.step(state, label) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        self.is_accepting(state)
+    }
+}
+
+#[derive(Clone)]
+enum Ast {
+    Label(String),
+    Any,
+    Concat(Box<Ast>, Box<Ast>),
+    Alternation(Box<Ast>, Box<Ast>),
+    Star(Box<Ast>),
+    Repeat(Box<Ast>, usize, Option<usize>),
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> RegexError {
+        RegexError { position: self.position, message: message.to_string() }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.position).is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.position).copied()
+    }
+
+    fn alternation(&mut self) -> Result<Ast, RegexError> {
+        let mut ast = self.concatenation()?;
+        while self.peek() == Some('|') {
+            self.position += 1;
+            ast = Ast::Alternation(Box::new(ast), Box::new(self.concatenation()?));
+        }
+        Ok(ast)
+    }
+
+    fn concatenation(&mut self) -> Result<Ast, RegexError> {
+        let mut ast = self.repetition()?;
+        loop {
+            match self.peek() {
+                Some('.') | Some('·') => {
+                    self.position += 1;
+                    ast = Ast::Concat(Box::new(ast), Box::new(self.repetition()?));
+                }
+                Some(c) if c == '(' || is_label_char(c) => {
+                    ast = Ast::Concat(Box::new(ast), Box::new(self.repetition()?));
+                }
+                _ => return Ok(ast),
+            }
+        }
+    }
+
+    fn repetition(&mut self) -> Result<Ast, RegexError> {
+        let mut ast = self.atom()?;
+        loop {
+            match self.peek() {
+                Some('*') => ast = Ast::Star(Box::new(ast)),
+                Some('+') => ast = Ast::Repeat(Box::new(ast), 1, None),
+                Some('?') => ast = Ast::Repeat(Box::new(ast), 0, Some(1)),
+                Some('{') => {
+                    self.position += 1;
+                    let min = self.number()?;
+                    let max = match self.peek() {
+                        Some(',') => {
+                            self.position += 1;
+                            if self.peek() == Some('}') { None } else { Some(self.number()?) }
+                        }
+                        _ => Some(min),
+                    };
+                    if self.peek() != Some('}') {
+                        return Err(self.error("expected '}'"));
+                    }
+                    if max.is_some_and(|max| max < min) {
+                        return Err(self.error("empty repetition range"));
+                    }
+                    ast = Ast::Repeat(Box::new(ast), min, max);
+                }
+                _ => return Ok(ast),
+            }
+            self.position += 1;
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, RegexError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.chars.get(self.position).is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits.parse().map_err(|_| RegexError { position: start, message: "expected a number".to_string() })
+    }
+
+    fn atom(&mut self) -> Result<Ast, RegexError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let ast = self.alternation()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("expected ')'"));
+                }
+                self.position += 1;
+                Ok(ast)
+            }
+            Some(c) if is_label_char(c) => {
+                let start = self.position;
+                while self.chars.get(self.position).is_some_and(|&c| is_label_char(c)) {
+                    self.position += 1;
+                }
+                let label: String = self.chars[start..self.position].iter().collect();
+                Ok(if label == "_" { Ast::Any } else { Ast::Label(label) })
+            }
+            Some(_) => Err(self.error("expected a label or '('")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+fn is_label_char(c: char) -> bool {
+    !c.is_whitespace() && !"()|*+?{},.·".contains(c)
+}
+
+/// A Thompson automaton, with `None` for the ε-transitions and `Some(None)` for the wildcard.
+#[derive(Default)]
+struct Nfa {
+    transitions: Vec<Vec<(Option<Option<String>>, usize)>>,
+}
+
+impl Nfa {
+    fn state(&mut self) -> usize {
+        self.transitions.push(Vec::new());
+        self.transitions.len() - 1
+    }
+
+    /// Adds the states of `ast`, returning its start and end states.
+    fn compile(&mut self, ast: &Ast) -> (usize, usize) {
+        let (start, end) = (self.state(), self.state());
+        match ast {
+            Ast::Label(label) => self.transitions[start].push((Some(Some(label.clone())), end)),
+            Ast::Any => self.transitions[start].push((Some(None), end)),
+            Ast::Concat(left, right) => {
+                let (left_start, left_end) = self.compile(left);
+                let (right_start, right_end) = self.compile(right);
+                self.transitions[start].push((None, left_start));
+                self.transitions[left_end].push((None, right_start));
+                self.transitions[right_end].push((None, end));
+            }
+            Ast::Alternation(left, right) => {
+                for ast in [left, right] {
+                    let (inner_start, inner_end) = self.compile(ast);
+                    self.transitions[start].push((None, inner_start));
+                    self.transitions[inner_end].push((None, end));
+                }
+            }
+            Ast::Star(inner) => {
+                let (inner_start, inner_end) = self.compile(inner);
+                self.transitions[start].push((None, inner_start));
+                self.transitions[start].push((None, end));
+                self.transitions[inner_end].push((None, inner_start));
+                self.transitions[inner_end].push((None, end));
+            }
+            Ast::Repeat(inner, min, max) => {
+                // r{m,n} = r^m (r?)^(n-m), and r{m,} = r^m r*.
+                let mut current = start;
+                for _ in 0..*min {
+                    let (inner_start, inner_end) = self.compile(inner);
+                    self.transitions[current].push((None, inner_start));
+                    current = inner_end;
+                }
+                match max {
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let (inner_start, inner_end) = self.compile(inner);
+                            self.transitions[current].push((None, inner_start));
+                            self.transitions[current].push((None, end));
+                            current = inner_end;
+                        }
+                    }
+                    None => {
+                        let (inner_start, inner_end) = self.compile(&Ast::Star(inner.clone()));
+                        self.transitions[current].push((None, inner_start));
+                        current = inner_end;
+                    }
+                }
+                self.transitions[current].push((None, end));
+            }
+        }
+        (start, end)
+    }
+
+    fn closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure: BTreeSet<usize> = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(q) = stack.pop() {
+            if closure.insert(q) {
+                stack.extend(self.transitions[q].iter().filter(|(label, _)| label.is_none()).map(|&(_, next)| next));
+            }
+        }
+        closure
+    }
+
+    /// The subset construction, with an explicit transition for every label of the expression
+    /// and a wildcard transition for all the other labels.
+    fn determinize(&self, start: usize, end: usize) -> EdgeRegex {
+        let mut regex = EdgeRegex { transitions: Vec::new(), wildcard: Vec::new(), accepting: Vec::new() };
+        let mut index: HashMap<BTreeSet<usize>, usize> = HashMap::new();
+        let mut subsets: Vec<BTreeSet<usize>> = Vec::new();
+        let mut add = |subset: BTreeSet<usize>, regex: &mut EdgeRegex, subsets: &mut Vec<BTreeSet<usize>>| {
+            *index.entry(subset.clone()).or_insert_with(|| {
+                regex.transitions.push(HashMap::new());
+                regex.wildcard.push(None);
+                regex.accepting.push(subset.contains(&end));
+                subsets.push(subset);
+                subsets.len() - 1
+            })
+        };
+        add(self.closure([start]), &mut regex, &mut subsets);
+
+        let mut q = 0;
+        while q < subsets.len() {
+            let subset = subsets[q].clone();
+            let moves = |label: &Option<String>| -> BTreeSet<usize> {
+                let next = subset.iter().flat_map(|&s| self.transitions[s].iter()).filter_map(|(l, next)| match l {
+                    Some(None) => Some(*next),
+                    Some(Some(l)) if Some(l) == label.as_ref() => Some(*next),
+                    _ => None,
+                });
+                self.closure(next)
+            };
+            let labels: BTreeSet<String> = subset.iter()
+                .flat_map(|&s| self.transitions[s].iter())
+                .filter_map(|(label, _)| label.clone().flatten())
+                .collect();
+            for label in labels {
+                let next = moves(&Some(label.clone()));
+                if !next.is_empty() {
+                    let next = add(next, &mut regex, &mut subsets);
+                    regex.transitions[q].insert(label, next);
+                }
+            }
+            let any = moves(&None);
+            if !any.is_empty() {
+                regex.wildcard[q] = Some(add(any, &mut regex, &mut subsets));
+            }
+            q += 1;
+        }
+        regex
+    }
+}
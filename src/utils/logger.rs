@@ -1,4 +1,4 @@
-use std::{env, error::Error, fs::File, io, sync::{Mutex, OnceLock}};
+use std::{env, error::Error, fs::{self, File}, io, path::Path, sync::{Mutex, OnceLock}};
 use env_logger::Target;
 use log::LevelFilter;
 use serde::{Serialize, Deserialize};
@@ -29,8 +29,13 @@ impl io::Write for MultiWriter {
 static LOGGER_INIT: OnceLock<()> = OnceLock::new();
 
 // 3. 安全的全局初始化函数
-pub fn init_global_logger_once(output_file: &'static str) {
+// 只有第一次调用的 output_file 生效；所在目录不存在时会被创建
+pub fn init_global_logger_once(output_file: impl AsRef<Path>) {
     LOGGER_INIT.get_or_init(|| {
+        let output_file = output_file.as_ref();
+        if let Some(dir) = output_file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).expect("Failed to create log directory");
+        }
         let log_file = File::create(output_file)
             .expect("Failed to create log file");
         
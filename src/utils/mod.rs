@@ -1,3 +1,5 @@
 pub mod predicate;
 pub mod validation;
-pub mod logger;
\ No newline at end of file
+pub mod logger;
+pub mod parallel;
+pub mod automaton;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// `Send + Sync` with the `parallel` feature, so that graphs, their nodes and the closures over them
+/// can be shared between rayon workers. Without the feature every type implements it.
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync + ?Sized> MaybeSync for T {}
+
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// The nodes of `nodes` accepted by `keep`, tested on all cores with the `parallel` feature.
+pub(crate) fn par_filter<'a, N>(nodes: impl Iterator<Item = &'a N>, keep: impl Fn(&'a N) -> bool + MaybeSync) -> HashSet<&'a N>
+where N: Eq + Hash + MaybeSync + 'a {
+    #[cfg(feature = "parallel")]
+    {
+        nodes.collect::<Vec<_>>().into_par_iter().filter(|&v| keep(v)).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        nodes.filter(|&v| keep(v)).collect()
+    }
+}
+
+/// `items.into_iter().map(f).collect()`, on all cores with the `parallel` feature. The order of `items` is kept.
+pub(crate) fn par_map<I, R>(items: Vec<I>, f: impl Fn(I) -> R + MaybeSync) -> Vec<R>
+where I: MaybeSync, R: MaybeSync {
+    #[cfg(feature = "parallel")]
+    {
+        items.into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use common::random_graph;
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_base::interfaces::labeled::{Label, Labeled};
+use graph_simulation::algorithm::bisimulation::Bisimulation;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+/// Naive bisimulation: refine by (label, set of successor classes) until nothing changes.
+fn naive_classes(graphs: &[&StandardLabeledGraph]) -> HashMap<(usize, usize), usize> {
+    let mut class: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut labels: HashMap<String, usize> = HashMap::new();
+    for (g, graph) in graphs.iter().enumerate() {
+        for v in graph.nodes() {
+            let next = labels.len();
+            class.insert((g, v.id()), *labels.entry(graph.get_label(v).label().to_string()).or_insert(next));
+        }
+    }
+    loop {
+        let mut signatures: HashMap<(usize, BTreeSet<usize>), usize> = HashMap::new();
+        let mut next_class = HashMap::new();
+        for (g, graph) in graphs.iter().enumerate() {
+            let mut post: HashMap<usize, BTreeSet<usize>> = HashMap::new();
+            for (v, v_prime) in graph.get_edges_pair() {
+                post.entry(v.id()).or_default().insert(class[&(g, v_prime.id())]);
+            }
+            for v in graph.nodes() {
+                let signature = (class[&(g, v.id())], post.remove(&v.id()).unwrap_or_default());
+                let len = signatures.len();
+                next_class.insert((g, v.id()), *signatures.entry(signature).or_insert(len));
+            }
+        }
+        let stable = signatures.len() == class.values().collect::<HashSet<_>>().len();
+        class = next_class;
+        if stable {
+            return class;
+        }
+    }
+}
+
+#[test]
+fn bisimulation_partition_same() {
+    let mut rng = Pcg64::seed_from_u64(7);
+    for _ in 0..100 {
+        let graph = random_graph(&mut rng, 30, 45, 2);
+        let expected = naive_classes(&[&graph]);
+
+        let partition = graph.bisimulation_partition();
+        assert_eq!(partition.iter().map(|class| class.len()).sum::<usize>(), 30);
+        assert_eq!(partition.len(), expected.values().collect::<HashSet<_>>().len());
+        for class in &partition {
+            let ids: HashSet<_> = class.iter().map(|v| expected[&(0, v.id())]).collect();
+            assert_eq!(ids.len(), 1);
+        }
+    }
+}
+
+#[test]
+fn bisimulation_inter_same() {
+    let mut rng = Pcg64::seed_from_u64(11);
+    for _ in 0..100 {
+        let graph1 = random_graph(&mut rng, 10, 15, 2);
+        let graph2 = random_graph(&mut rng, 20, 30, 2);
+        let expected = naive_classes(&[&graph1, &graph2]);
+
+        let bisimulation = graph1.get_bisimulation(&graph2);
+        for u in graph1.nodes() {
+            let ids: HashSet<_> = bisimulation[u].iter().map(|v| v.id()).collect();
+            let expected_ids: HashSet<_> = graph2.nodes().filter(|v| expected[&(1, v.id())] == expected[&(0, u.id())]).map(|v| v.id()).collect();
+            assert_eq!(ids, expected_ids);
+        }
+    }
+}
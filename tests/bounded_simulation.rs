@@ -0,0 +1,187 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::ids;
+use graph_base::impls::standard::{LabelNode, LabeledEdge, SingleLabel, StandardLabeledGraph};
+use graph_base::interfaces::graph::{Adjacency, AdjacencyInv, Degree, Directed, Graph, SingleId};
+use graph_base::interfaces::labeled::{Label, Labeled};
+use graph_simulation::algorithm::bounded::{Bound, Bounded, BoundedSimulation, Reachability};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// A `StandardLabeledGraph` whose edges carry a bound, at most 1 unless set otherwise.
+struct BoundedGraph {
+    graph: StandardLabeledGraph,
+    bounds: HashMap<(usize, usize), Bound>,
+}
+
+impl BoundedGraph {
+    fn build(nodes: &[(u64, &str)], edges: &[(u64, u64, Bound)]) -> Self {
+        let mut graph = <StandardLabeledGraph as Graph>::new();
+        for (node, label) in nodes {
+            graph.add_node(*node, label.to_string());
+        }
+        let mut bounds = HashMap::new();
+        for &(source, destination, bound) in edges {
+            graph.add_edge(source, destination);
+            bounds.insert((source as usize, destination as usize), bound);
+        }
+        BoundedGraph { graph, bounds }
+    }
+
+    fn random(rng: &mut impl Rng, n: u64, m: usize, labels: u64, max_bound: usize) -> Self {
+        Self::random_with(rng, n, m, labels, |rng| Bound::AtMost(rng.random_range(1..=max_bound)))
+    }
+
+    /// Random bounds of every kind, up to `max_bound`.
+    fn random_kinds(rng: &mut impl Rng, n: u64, m: usize, labels: u64, max_bound: usize) -> Self {
+        Self::random_with(rng, n, m, labels, |rng| {
+            let (lo, hi) = (rng.random_range(0..=max_bound), rng.random_range(1..=max_bound));
+            match rng.random_range(0..4) {
+                0 => Bound::AtMost(hi),
+                1 => Bound::Exactly(hi),
+                2 => Bound::Range(lo.min(hi), hi),
+                _ => Bound::Unbounded,
+            }
+        })
+    }
+
+    fn random_with<R: Rng>(rng: &mut R, n: u64, m: usize, labels: u64, mut bound: impl FnMut(&mut R) -> Bound) -> Self {
+        let nodes: Vec<(u64, String)> = (0..n).map(|node| (node, rng.random_range(0..labels).to_string())).collect();
+        let edges: Vec<(u64, u64, Bound)> = (0..m).map(|_| {
+            (rng.random_range(0..n), rng.random_range(0..n), bound(rng))
+        }).collect();
+        let nodes: Vec<(u64, &str)> = nodes.iter().map(|(node, label)| (*node, label.as_str())).collect();
+        Self::build(&nodes, &edges)
+    }
+}
+
+impl<'a> Graph<'a> for BoundedGraph {
+    type Node = LabelNode<String>;
+    type Edge = LabeledEdge<SingleLabel>;
+
+    fn new() -> Self {
+        BoundedGraph { graph: <StandardLabeledGraph as Graph>::new(), bounds: HashMap::new() }
+    }
+
+    fn nodes(&'a self) -> impl Iterator<Item = &'a Self::Node> {
+        self.graph.nodes()
+    }
+
+    fn edges(&'a self) -> impl Iterator<Item = &'a Self::Edge> {
+        self.graph.edges()
+    }
+
+    fn add_node(&mut self, node: Self::Node) {
+        Graph::add_node(&mut self.graph, node);
+    }
+
+    fn add_edge(&mut self, edge: Self::Edge) {
+        Graph::add_edge(&mut self.graph, edge);
+    }
+}
+
+impl<'a> Labeled<'a> for BoundedGraph {
+    fn label_same(&self, node: &Self::Node, label: &Self::Node) -> bool {
+        self.graph.label_same(node, label)
+    }
+
+    fn get_label(&'a self, node: &'a Self::Node) -> &'a impl Label {
+        self.graph.get_label(node)
+    }
+
+    fn get_edges_pair_label(&'a self) -> impl Iterator<Item = (&'a Self::Node, &'a Self::Node, &'a impl Label)> {
+        self.graph.get_edges_pair_label()
+    }
+
+    fn edge_label_same(&self, edge1: &Self::Edge, edge2: &Self::Edge) -> bool {
+        self.graph.edge_label_same(edge1, edge2)
+    }
+
+    fn edge_node_label_same(&self, src1: &Self::Node, edge1: &Self::Edge, dst1: &Self::Node, src2: &Self::Node, edge2: &Self::Edge, dst2: &Self::Node) -> bool {
+        self.graph.edge_node_label_same(src1, edge1, dst1, src2, edge2, dst2)
+    }
+}
+
+impl Directed for BoundedGraph {}
+
+impl Adjacency<'_> for BoundedGraph {}
+
+impl AdjacencyInv<'_> for BoundedGraph {}
+
+impl Degree<'_> for BoundedGraph {}
+
+impl<'a> Bounded<'a> for BoundedGraph {
+    fn get_bound(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> usize {
+        self.get_bound_range(u, v).limits().1.unwrap_or(usize::MAX)
+    }
+
+    fn get_bound_range(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> Bound {
+        self.bounds.get(&(u.id(), v.id())).copied().unwrap_or(Bound::AtMost(1))
+    }
+}
+
+#[test]
+fn bounded_simulation_dense_same() {
+    let mut rng = Pcg64::seed_from_u64(5);
+    for _ in 0..200 {
+        let pattern = BoundedGraph::random(&mut rng, 4, 5, 2, 3);
+        let data = BoundedGraph::random(&mut rng, 20, 30, 2, 1);
+
+        assert_eq!(ids(&pattern.get_bounded_simulation_matrix(&data)), ids(&pattern.get_bounded_simulation_dense(&data)));
+    }
+}
+
+#[test]
+fn bounded_simulation_explained_same() {
+    let mut rng = Pcg64::seed_from_u64(6);
+    for _ in 0..200 {
+        let pattern = BoundedGraph::random(&mut rng, 4, 5, 2, 3);
+        let data = BoundedGraph::random(&mut rng, 20, 30, 2, 1);
+
+        let explanation = pattern.get_bounded_simulation_explained(&data);
+        let sim = pattern.get_bounded_simulation_matrix(&data);
+        assert_eq!(ids(explanation.relation()), ids(&sim));
+        assert_eq!(explanation.emptied().is_some(), sim.is_empty());
+        for u in pattern.nodes().filter(|_| !sim.is_empty()) {
+            for v in data.nodes().filter(|v| !sim[u].contains(v)) {
+                assert!(explanation.removal(u, v).is_some());
+            }
+        }
+    }
+}
+
+#[test]
+fn bounded_simulation_reachability_same() {
+    let mut rng = Pcg64::seed_from_u64(8);
+    for i in 0..100 {
+        let pattern = BoundedGraph::random(&mut rng, 4, 5, 2, 4);
+        let data = if i % 10 == 0 {
+            BoundedGraph::random(&mut rng, 200, 260, 2, 1)
+        } else {
+            BoundedGraph::random(&mut rng, 20, 30, 2, 1)
+        };
+
+        let expected = ids(&pattern.get_bounded_simulation_matrix(&data));
+        assert_eq!(ids(&pattern.get_bounded_simulation(&data)), expected);
+        for reachability in [Reachability::Full, Reachability::OnDemand, Reachability::Landmarks(1), Reachability::Landmarks(4)] {
+            assert_eq!(ids(&pattern.get_bounded_simulation_with(&data, reachability)), expected);
+        }
+    }
+}
+
+#[test]
+fn bounded_simulation_kinds_same() {
+    let mut rng = Pcg64::seed_from_u64(9);
+    for _ in 0..200 {
+        let pattern = BoundedGraph::random_kinds(&mut rng, 4, 5, 2, 3);
+        let data = BoundedGraph::random(&mut rng, 20, 30, 2, 1);
+
+        let expected = ids(&pattern.get_bounded_simulation_matrix(&data));
+        for reachability in [Reachability::Full, Reachability::OnDemand, Reachability::Landmarks(2)] {
+            assert_eq!(ids(&pattern.get_bounded_simulation_with(&data, reachability)), expected);
+        }
+        assert_eq!(ids(pattern.get_bounded_simulation_explained(&data).relation()), expected);
+    }
+}
@@ -0,0 +1,216 @@
+//! Typed hypergraphs shared by the hyper simulation tests.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use graph_base::interfaces::edge::Hyperedge;
+use graph_base::interfaces::graph::SingleId;
+use graph_base::interfaces::hypergraph::{ContainedHyperedge, Hypergraph, IdVector};
+use graph_base::interfaces::typed::Typed;
+use graph_base::interfaces::vertex::Vertex;
+use graph_simulation::algorithm::hyper_simulation::{ClusterDMatch, DMatch, Delta, LPredicate, SematicCluster};
+use rand::Rng;
+
+/// A hypergraph node with a type.
+#[derive(Hash, Eq, PartialEq, Clone)]
+pub struct TypedNode {
+    pub id: usize,
+    pub node_type: usize,
+}
+
+impl Display for TypedNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[id: {}, type: {}]", self.id, self.node_type)
+    }
+}
+
+impl SingleId for TypedNode {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Vertex for TypedNode {}
+
+/// A hyperedge with a label over the node ids.
+#[derive(Hash, Eq, PartialEq, Clone)]
+pub struct LabeledHyperedge {
+    pub label: usize,
+    pub nodes: Vec<usize>,
+}
+
+impl IdVector for LabeledHyperedge {
+    fn id(&self) -> Vec<usize> {
+        self.nodes.clone()
+    }
+}
+
+impl Hyperedge for LabeledHyperedge {
+    fn id_set(&self) -> HashSet<usize> {
+        self.nodes.iter().copied().collect()
+    }
+}
+
+/// A hypergraph whose nodes match by type and whose hyperedges match by label.
+pub struct TypedHypergraph {
+    pub nodes: Vec<TypedNode>,
+    pub edges: Vec<LabeledHyperedge>,
+}
+
+impl<'a> Hypergraph<'a> for TypedHypergraph {
+    type Node = TypedNode;
+    type Edge = LabeledHyperedge;
+
+    fn new() -> Self {
+        TypedHypergraph { nodes: Vec::new(), edges: Vec::new() }
+    }
+
+    fn nodes(&'a self) -> impl Iterator<Item = &'a Self::Node> {
+        self.nodes.iter()
+    }
+
+    fn hyperedges(&'a self) -> impl Iterator<Item = &'a Self::Edge> {
+        self.edges.iter()
+    }
+
+    fn get_node_by_id(&'a self, id: usize) -> Option<&'a Self::Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn add_node(&mut self, node: Self::Node) {
+        self.nodes.push(node);
+    }
+
+    fn add_hyperedge(&mut self, edge: Self::Edge) {
+        self.edges.push(edge);
+    }
+}
+
+impl<'a> Typed<'a> for TypedHypergraph {
+    fn type_same(&self, x: &Self::Node, y: &Self::Node) -> bool {
+        x.node_type == y.node_type
+    }
+}
+
+impl<'a> LPredicate<'a> for TypedHypergraph {
+    fn l_predicate_node(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> bool {
+        u.node_type == v.node_type
+    }
+
+    fn l_predicate_edge(&'a self, e: &'a Self::Edge, e_prime: &'a Self::Edge) -> bool {
+        e.label == e_prime.label
+    }
+
+    fn l_predicate_set(&'a self, x: &HashSet<&'a Self::Node>, y: &HashSet<&'a Self::Node>) -> bool {
+        x.iter().all(|u| y.iter().any(|v| u.node_type == v.node_type))
+    }
+}
+
+impl<'a> ContainedHyperedge<'a> for TypedHypergraph {}
+
+impl TypedHypergraph {
+    /// `n` nodes typed in 0..types and `e` hyperedges of 1 to 3 distinct nodes labeled in 0..labels.
+    pub fn random(rng: &mut impl Rng, n: usize, e: usize, types: usize, labels: usize) -> Self {
+        let mut graph = <Self as Hypergraph>::new();
+        for id in 0..n {
+            graph.add_node(TypedNode { id, node_type: rng.random_range(0..types) });
+        }
+        for _ in 0..e {
+            let size = rng.random_range(1..=3.min(n));
+            let mut nodes: Vec<usize> = Vec::new();
+            while nodes.len() < size {
+                let id = rng.random_range(0..n);
+                if !nodes.contains(&id) {
+                    nodes.push(id);
+                }
+            }
+            graph.add_hyperedge(LabeledHyperedge { label: rng.random_range(0..labels), nodes });
+        }
+        graph
+    }
+}
+
+/// The pairs (u_id, v_id) of `sim`.
+pub fn by_id(sim: &HashMap<&TypedNode, HashSet<&TypedNode>>) -> HashSet<(usize, usize)> {
+    sim.iter().flat_map(|(u, v_set)| v_set.iter().map(move |v| (u.id, v.id))).collect()
+}
+
+/// A pair of node or cluster ids.
+pub type Pair = (usize, usize);
+
+/// The semantic cluster pairs of a pair of nodes.
+pub type ClusterPairs<'a> = Vec<(SematicCluster<'a, LabeledHyperedge>, SematicCluster<'a, LabeledHyperedge>)>;
+
+/// Random semantic clusters for every pair of nodes, drawn from a few cluster ids so that pairs share clusters, and a
+/// random D-match for every cluster pair. A pair is in the D-match of its own clusters most of the time.
+pub struct RandomClusters<'a> {
+    pub clusters: HashMap<(usize, usize), ClusterPairs<'a>>,
+    pub d_match: HashMap<(usize, usize), HashSet<(usize, usize)>>,
+    empty_clusters: ClusterPairs<'a>,
+    empty_d_match: HashSet<(usize, usize)>,
+}
+
+impl<'a> RandomClusters<'a> {
+    pub fn random(rng: &mut impl Rng, pattern: &TypedHypergraph, data: &TypedHypergraph, cluster_ids: usize) -> Self {
+        let mut clusters: HashMap<(usize, usize), Vec<_>> = HashMap::new();
+        let mut d_match: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
+        for u in &pattern.nodes {
+            for v in &data.nodes {
+                for _ in 0..rng.random_range(0..3) {
+                    let c_pair = (rng.random_range(0..cluster_ids), rng.random_range(0..cluster_ids));
+                    let d_match_set = d_match.entry(c_pair).or_insert_with(|| {
+                        (0..rng.random_range(0..4)).map(|_| (rng.random_range(0..pattern.nodes.len()), rng.random_range(0..data.nodes.len()))).collect()
+                    });
+                    if rng.random_bool(0.9) {
+                        d_match_set.insert((u.id, v.id));
+                    }
+                    clusters.entry((u.id, v.id)).or_default().push((SematicCluster::new(c_pair.0, Vec::new()), SematicCluster::new(c_pair.1, Vec::new())));
+                }
+            }
+        }
+        RandomClusters { clusters, d_match, empty_clusters: Vec::new(), empty_d_match: HashSet::new() }
+    }
+
+    /// The clusters of the listed pairs, one cluster pair each, and the listed D-matches.
+    pub fn build(clusters: &[(Pair, Pair)], d_match: &[(Pair, &[Pair])]) -> Self {
+        let mut by_pair: HashMap<(usize, usize), Vec<_>> = HashMap::new();
+        for &(pair, c_pair) in clusters {
+            by_pair.entry(pair).or_default().push((SematicCluster::new(c_pair.0, Vec::new()), SematicCluster::new(c_pair.1, Vec::new())));
+        }
+        let d_match = d_match.iter().map(|&(c_pair, pairs)| (c_pair, pairs.iter().copied().collect())).collect();
+        RandomClusters { clusters: by_pair, d_match, empty_clusters: Vec::new(), empty_d_match: HashSet::new() }
+    }
+
+    /// The `hc_map` of `get_hyper_simulation_effect_by_id`.
+    pub fn hc_map(&self, pattern: &TypedHypergraph, data: &TypedHypergraph) -> HashMap<(usize, usize), Vec<ClusterDMatch>> {
+        let mut hc_map = HashMap::new();
+        for u in &pattern.nodes {
+            for v in data.nodes.iter().filter(|v| v.node_type == u.node_type) {
+                let clusters: Vec<_> = self.clusters.get(&(u.id, v.id)).into_iter().flatten()
+                    .map(|(cluster_u, cluster_v)| ((cluster_u.id(), cluster_v.id()), self.d_match(cluster_u, cluster_v).clone()))
+                    .collect();
+                if clusters.iter().all(|(_, d_match_set)| d_match_set.contains(&(u.id, v.id))) {
+                    hc_map.insert((u.id, v.id), clusters);
+                }
+            }
+        }
+        hc_map
+    }
+}
+
+impl<'a> Delta<'a> for RandomClusters<'a> {
+    type Node = TypedNode;
+    type Edge = LabeledHyperedge;
+
+    fn get_sematic_clusters(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> &'a Vec<(SematicCluster<'a, Self::Edge>, SematicCluster<'a, Self::Edge>)> {
+        self.clusters.get(&(u.id, v.id)).unwrap_or(&self.empty_clusters)
+    }
+}
+
+impl<'a> DMatch<'a> for RandomClusters<'a> {
+    type Edge = LabeledHyperedge;
+
+    fn d_match(&self, e: &SematicCluster<'a, Self::Edge>, e_prime: &SematicCluster<'a, Self::Edge>) -> &HashSet<(usize, usize)> {
+        self.d_match.get(&(e.id(), e_prime.id())).unwrap_or(&self.empty_d_match)
+    }
+}
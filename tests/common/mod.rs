@@ -0,0 +1,34 @@
+//! Graph builders and relation helpers shared by the integration tests.
+#![allow(dead_code)]
+
+pub mod hyper;
+
+use std::collections::{HashMap, HashSet};
+
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use rand::Rng;
+
+/// The graph with the given nodes (id, label) and edges (source, destination).
+pub fn build_graph<L: AsRef<str>>(nodes: &[(u64, L)], edges: &[(u64, u64)]) -> StandardLabeledGraph {
+    let mut graph = <StandardLabeledGraph as Graph>::new();
+    for (node, label) in nodes {
+        graph.add_node(*node, label.as_ref().to_string());
+    }
+    for &(source, destination) in edges {
+        graph.add_edge(source, destination);
+    }
+    graph
+}
+
+/// A graph of `n` nodes labeled in 0..labels and `m` edges, which may be loops or repeated.
+pub fn random_graph(rng: &mut impl Rng, n: u64, m: usize, labels: u64) -> StandardLabeledGraph {
+    let nodes: Vec<(u64, String)> = (0..n).map(|node| (node, rng.random_range(0..labels).to_string())).collect();
+    let edges: Vec<(u64, u64)> = (0..m).map(|_| (rng.random_range(0..n), rng.random_range(0..n))).collect();
+    build_graph(&nodes, &edges)
+}
+
+/// The relation `sim` by node ids.
+pub fn ids<N: SingleId>(sim: &HashMap<&N, HashSet<&N>>) -> HashMap<usize, HashSet<usize>> {
+    sim.iter().map(|(u, sim_u)| (u.id(), sim_u.iter().map(|v| v.id()).collect())).collect()
+}
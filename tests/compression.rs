@@ -0,0 +1,47 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{build_graph, ids, random_graph};
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::compress::Compression;
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn compression_example() {
+    // Three A nodes with a B leaf each, and an A with an A child.
+    let data = build_graph(
+        &[(0, "A"), (1, "B"), (2, "A"), (3, "B"), (4, "A"), (5, "B"), (6, "A")],
+        &[(0, 1), (2, 3), (4, 5), (6, 0)],
+    );
+    let compressed = data.compress();
+    assert_eq!(compressed.len(), 3);
+    assert_eq!(compressed.graph().get_edges_pair().count(), 2);
+    let class = |id: usize| compressed.class_of(data.nodes().find(|v| v.id() == id).unwrap());
+    assert!(class(0) == class(2) && class(2) == class(4) && class(1) == class(3) && class(0) != class(6));
+    let members: Vec<usize> = compressed.members(compressed.graph().nodes().find(|c| c.id() == class(0)).unwrap()).iter().map(|v| v.id()).collect();
+    assert_eq!(members, vec![0, 2, 4]);
+
+    let pattern = build_graph(&[(0, "A"), (1, "B")], &[(0, 1)]);
+    let sim = ids(&compressed.simulate(&pattern, Simulation::get_simulation_hhk));
+    assert_eq!(sim[&0], HashSet::from([0, 2, 4]));
+    assert_eq!(sim[&1], HashSet::from([1, 3, 5]));
+}
+
+#[test]
+fn compression_same() {
+    let mut rng = Pcg64::seed_from_u64(20);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 4, 5, 2);
+        let data = random_graph(&mut rng, 30, 40, 2);
+        let compressed = data.compress();
+        assert!(compressed.len() <= data.nodes().count());
+
+        let expected = ids(&pattern.get_simulation_hhk(&data));
+        assert!(ids(&compressed.simulate(&pattern, Simulation::get_simulation_hhk)) == expected);
+        assert!(ids(&compressed.simulate(&pattern, Simulation::get_simulation_inter)) == expected);
+        assert!(ids(&compressed.simulate(&pattern, |p, d| p.get_simulation_inter_dense(d))) == expected);
+    }
+}
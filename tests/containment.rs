@@ -0,0 +1,71 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{build_graph, random_graph};
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::containment::{Containment, QueryContainment};
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn containment_example() {
+    let q1 = build_graph(&[(0, "A"), (1, "B")], &[(0, 1)]);
+    let q2 = build_graph(&[(0, "A"), (1, "B"), (2, "B")], &[(0, 1), (0, 2), (1, 2)]);
+    let q3 = build_graph(&[(0, "A"), (1, "B"), (2, "C")], &[(0, 1), (0, 2)]);
+
+    // A with a B child, and a B with a B child: both are matches of A -> B.
+    let containment = q1.get_containment(&q2);
+    assert!(q1.contains(&q2));
+    let witness = containment.witness().unwrap();
+    for v in q2.nodes() {
+        let expected: HashSet<usize> = if v.id() == 0 { HashSet::from([0]) } else { HashSet::from([1]) };
+        assert_eq!(witness[v].iter().map(|u| u.id()).collect::<HashSet<_>>(), expected);
+    }
+
+    // The B of q1 has no B child, so neither the first B of q2 nor its A parent has a match in q1.
+    assert!(matches!(q2.get_containment(&q1), Containment::Unmatched(u) if u.id() == 0));
+    // q1 has no C child, so neither the A nor the C of q3 has a match in it.
+    assert!(matches!(q3.get_containment(&q1), Containment::Unmatched(u) if u.id() == 0));
+    // The C node of q3 matches no node of q1.
+    assert!(matches!(q1.get_containment(&q3), Containment::Uncovered(v) if v.id() == 2));
+    assert_eq!(q1.get_containment(&q3).counterexample().map(|v| v.id()), Some(2));
+}
+
+#[test]
+fn containment_holds_on_data() {
+    let mut rng = Pcg64::seed_from_u64(19);
+    let (mut contained, mut checked) = (0, 0);
+    for _ in 0..300 {
+        let q1 = random_graph(&mut rng, 3, 3, 2);
+        let q2 = random_graph(&mut rng, 4, 6, 2);
+        let containment = q1.get_containment(&q2);
+        assert!(q1.contains(&q2) == containment.is_contained());
+        if !containment.is_contained() {
+            // The counterexample fails on q2 as the data graph.
+            let sim = q1.get_simulation_hhk(&q2);
+            match containment {
+                Containment::Unmatched(u) => assert!(sim[u].is_empty()),
+                Containment::Uncovered(v) => assert!(sim.values().all(|sim_u| !sim_u.contains(v))),
+                Containment::Contained { .. } => unreachable!(),
+            }
+            continue;
+        }
+        contained += 1;
+        for _ in 0..10 {
+            let data = random_graph(&mut rng, 10, 20, 2);
+            let sim2 = q2.get_simulation_hhk(&data);
+            if !StandardLabeledGraph::has_simulation(&sim2) {
+                continue;
+            }
+            checked += 1;
+            let sim1 = q1.get_simulation_hhk(&data);
+            assert!(sim1.values().all(|sim_u| !sim_u.is_empty()));
+            let matches1: HashSet<_> = sim1.values().flatten().collect();
+            assert!(sim2.values().flatten().all(|v| matches1.contains(v)));
+        }
+    }
+    assert!(contained > 0 && checked > 0, "{} {}", contained, checked);
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::hyper::{by_id, TypedNode};
+use graph_base::interfaces::edge::DirectedHyperedge;
+use graph_base::interfaces::hypergraph::{ContainedDirectedHyperedge, DirectedHypergraph, Hypergraph, IdVector};
+use graph_base::interfaces::typed::Typed;
+use graph_simulation::algorithm::hyper_simulation::{DirectedHyperSimulation, HyperSimulationOptions, LPredicate};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+#[derive(Hash, Eq, PartialEq, Clone)]
+struct DirectedLabeledHyperedge {
+    label: usize,
+    src: Vec<usize>,
+    dst: Vec<usize>,
+}
+
+impl IdVector for DirectedLabeledHyperedge {
+    fn id(&self) -> Vec<usize> {
+        self.src.iter().chain(&self.dst).copied().collect()
+    }
+}
+
+impl DirectedHyperedge for DirectedLabeledHyperedge {
+    fn src(&self) -> HashSet<usize> {
+        self.src.iter().copied().collect()
+    }
+
+    fn dst(&self) -> HashSet<usize> {
+        self.dst.iter().copied().collect()
+    }
+}
+
+struct DirectedTypedHypergraph {
+    nodes: Vec<TypedNode>,
+    edges: Vec<DirectedLabeledHyperedge>,
+}
+
+impl<'a> Hypergraph<'a> for DirectedTypedHypergraph {
+    type Node = TypedNode;
+    type Edge = DirectedLabeledHyperedge;
+
+    fn new() -> Self {
+        DirectedTypedHypergraph { nodes: Vec::new(), edges: Vec::new() }
+    }
+
+    fn nodes(&'a self) -> impl Iterator<Item = &'a Self::Node> {
+        self.nodes.iter()
+    }
+
+    fn hyperedges(&'a self) -> impl Iterator<Item = &'a Self::Edge> {
+        self.edges.iter()
+    }
+
+    fn get_node_by_id(&'a self, id: usize) -> Option<&'a Self::Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn add_node(&mut self, node: Self::Node) {
+        self.nodes.push(node);
+    }
+
+    fn add_hyperedge(&mut self, edge: Self::Edge) {
+        self.edges.push(edge);
+    }
+}
+
+impl<'a> DirectedHypergraph<'a> for DirectedTypedHypergraph {}
+
+impl<'a> ContainedDirectedHyperedge<'a> for DirectedTypedHypergraph {}
+
+impl<'a> Typed<'a> for DirectedTypedHypergraph {
+    fn type_same(&self, x: &Self::Node, y: &Self::Node) -> bool {
+        x.node_type == y.node_type
+    }
+}
+
+impl<'a> LPredicate<'a> for DirectedTypedHypergraph {
+    fn l_predicate_node(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> bool {
+        u.node_type == v.node_type
+    }
+
+    fn l_predicate_edge(&'a self, e: &'a Self::Edge, e_prime: &'a Self::Edge) -> bool {
+        e.label == e_prime.label
+    }
+
+    fn l_predicate_set(&'a self, x: &HashSet<&'a Self::Node>, y: &HashSet<&'a Self::Node>) -> bool {
+        x.iter().all(|u| y.iter().any(|v| u.node_type == v.node_type))
+    }
+}
+
+impl DirectedTypedHypergraph {
+    fn build(types: &[usize], edges: &[(usize, &[usize], &[usize])]) -> Self {
+        let mut graph = <Self as Hypergraph>::new();
+        for (id, &node_type) in types.iter().enumerate() {
+            graph.add_node(TypedNode { id, node_type });
+        }
+        for &(label, src, dst) in edges {
+            graph.add_hyperedge(DirectedLabeledHyperedge { label, src: src.to_vec(), dst: dst.to_vec() });
+        }
+        graph
+    }
+
+    fn random(rng: &mut impl Rng, n: usize, e: usize, types: usize, labels: usize) -> Self {
+        let mut graph = <Self as Hypergraph>::new();
+        for id in 0..n {
+            graph.nodes.push(TypedNode { id, node_type: rng.random_range(0..types) });
+        }
+        for _ in 0..e {
+            let size = rng.random_range(2..=3.min(n));
+            let mut nodes: Vec<usize> = Vec::new();
+            while nodes.len() < size {
+                let id = rng.random_range(0..n);
+                if !nodes.contains(&id) {
+                    nodes.push(id);
+                }
+            }
+            let split = rng.random_range(1..size);
+            let dst = nodes.split_off(split);
+            graph.edges.push(DirectedLabeledHyperedge { label: rng.random_range(0..labels), src: nodes, dst });
+        }
+        graph
+    }
+}
+
+#[test]
+fn directed_hyper_simulation_example() {
+    // Pattern: 0 -> {1, 2}. Data: 0 -> {1, 2} as in the pattern, and the reversed {4, 5} -> 3.
+    let options = HyperSimulationOptions::default();
+    let pattern = DirectedTypedHypergraph::build(&[0, 1, 1], &[(0, &[0], &[1, 2])]);
+    let data = DirectedTypedHypergraph::build(&[0, 1, 1, 0, 1, 1], &[(0, &[0], &[1, 2]), (0, &[4, 5], &[3])]);
+
+    let naive = by_id(&pattern.get_directed_hyper_simulation_naive(&data, &options));
+    let expected: HashSet<(usize, usize)> = HashSet::from([(0, 0), (1, 1), (1, 2), (2, 1), (2, 2)]);
+    assert_eq!(naive, expected);
+    assert_eq!(by_id(&pattern.get_directed_hyper_simulation_effect(&data, &options)), expected);
+
+    // A pattern node of the head needs its neighbours of the tail matched on the tail of the same data hyperedge.
+    let pattern = DirectedTypedHypergraph::build(&[0, 1], &[(0, &[0], &[1]), (1, &[1], &[0])]);
+    let data = DirectedTypedHypergraph::build(&[0, 1, 1], &[(0, &[0], &[1]), (1, &[2], &[0])]);
+    assert!(by_id(&pattern.get_directed_hyper_simulation_naive(&data, &options)).is_empty());
+    assert!(by_id(&pattern.get_directed_hyper_simulation_effect(&data, &options)).is_empty());
+}
+
+#[test]
+fn directed_hyper_simulation_same() {
+    let options = HyperSimulationOptions::default();
+    let mut rng = Pcg64::seed_from_u64(21);
+    for _ in 0..100 {
+        let pattern = DirectedTypedHypergraph::random(&mut rng, 4, 3, 2, 1);
+        let data = DirectedTypedHypergraph::random(&mut rng, 10, 30, 2, 1);
+
+        let naive = by_id(&pattern.get_directed_hyper_simulation_naive(&data, &options));
+        let effect = by_id(&pattern.get_directed_hyper_simulation_effect(&data, &options));
+        assert_eq!(naive, effect);
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{build_graph, ids};
+use graph_base::impls::standard::{LabelNode, StandardLabeledGraph};
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::explain::Reason;
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+fn node(graph: &StandardLabeledGraph, id: usize) -> &LabelNode<String> {
+    graph.nodes().find(|node| node.id() == id).unwrap()
+}
+
+#[test]
+fn explain_chain() {
+    let pattern = build_graph(&[(0, "A"), (1, "B"), (2, "C")], &[(0, 1), (1, 2)]);
+    let data = build_graph(&[(10, "A"), (11, "B"), (12, "D")], &[(10, 11), (11, 12)]);
+    let explanation = pattern.get_simulation_inter_explained(&data);
+    let (a, b, c) = (node(&pattern, 0), node(&pattern, 1), node(&pattern, 2));
+    let (x, y, z) = (node(&data, 10), node(&data, 11), node(&data, 12));
+
+    assert!(matches!(explanation.removal(c, z).unwrap().reason, Reason::LabelMismatch));
+    let removal = explanation.removal(a, x).unwrap();
+    assert!(matches!(removal.reason, Reason::Unmatched { child, bound: None, cause: Some((u, v)) } if child == b && u == b && v == y));
+    assert!(removal.round > explanation.removal(b, y).unwrap().round);
+
+    let lines: Vec<String> = explanation.explain(a, x).lines().map(String::from).collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("from sim([id: 0, label: A])") && lines[1].contains("from sim([id: 1, label: B])"));
+    assert!(lines[2].ends_with("their labels differ"));
+    assert!(explanation.relation().values().all(|sim_u| sim_u.is_empty()));
+}
+
+#[test]
+fn explained_simulation_same() {
+    let mut rng = Pcg64::seed_from_u64(7);
+    for _ in 0..100 {
+        let labels = ["0", "1"];
+        let pattern_nodes: Vec<(u64, &str)> = (0..4).map(|u| (u, labels[rng.random_range(0..2)])).collect();
+        let pattern_edges: Vec<(u64, u64)> = (0..4).map(|_| (rng.random_range(0..4), rng.random_range(0..4))).collect();
+        let data_nodes: Vec<(u64, &str)> = (0..15).map(|v| (v, labels[rng.random_range(0..2)])).collect();
+        let data_edges: Vec<(u64, u64)> = (0..20).map(|_| (rng.random_range(0..15), rng.random_range(0..15))).collect();
+        let pattern = build_graph(&pattern_nodes, &pattern_edges);
+        let data = build_graph(&data_nodes, &data_edges);
+
+        let explanation = pattern.get_simulation_inter_explained(&data);
+        assert_eq!(ids(explanation.relation()), ids(&pattern.get_simulation_inter(&data)));
+        for u in pattern.nodes() {
+            for v in data.nodes().filter(|v| !explanation.relation()[u].contains(v)) {
+                assert!(explanation.removal(u, v).is_some());
+                let derivation = explanation.explain(u, v);
+                assert!(derivation.ends_with("their labels differ") || derivation.ends_with("has none"));
+            }
+        }
+    }
+}
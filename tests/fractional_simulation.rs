@@ -0,0 +1,69 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::{build_graph, random_graph};
+use graph_base::impls::standard::LabelNode;
+use graph_base::interfaces::graph::SingleId;
+use graph_simulation::algorithm::fractional::{threshold, FractionalOptions, FractionalSimulation};
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+fn score_of(scores: &HashMap<(&LabelNode<String>, &LabelNode<String>), f64>, u: usize, v: usize) -> f64 {
+    scores.iter().find(|((x, y), _)| x.id() == u && y.id() == v).map(|(_, &score)| score).unwrap()
+}
+
+#[test]
+fn fractional_simulation_example() {
+    // A has children B and C, but the data A only has a B child.
+    let pattern = build_graph(&[(0, "A"), (1, "B"), (2, "C")], &[(0, 1), (0, 2)]);
+    let data = build_graph(&[(10, "A"), (11, "B"), (12, "D")], &[(10, 11)]);
+    let options = FractionalOptions { label_weight: 0.2, post_weight: 0.8, pre_weight: 0.0, epsilon: 0.0, max_rounds: 10 };
+    let scores = pattern.get_fractional_simulation(&data, options);
+
+    assert_eq!(scores.len(), 9);
+    assert!(scores.values().all(|&score| (0.0..=1.0).contains(&score)));
+    // Leaves only differ by their labels: 0.2 · label_same + 0.8.
+    assert!((score_of(&scores, 1, 11) - 1.0).abs() < 1e-12);
+    assert!((score_of(&scores, 2, 11) - 0.8).abs() < 1e-12);
+    // 0.2 + 0.8 · (1 + 0.8) / 2: B is matched, C only by the B child.
+    assert!((score_of(&scores, 0, 10) - 0.92).abs() < 1e-12);
+    // A data node without children matches no child: 0.2 · label_same only.
+    assert!((score_of(&scores, 0, 11) - 0.0).abs() < 1e-12);
+
+    let sim = threshold(&scores, 0.9);
+    assert_eq!(sim.len(), 3);
+    assert_eq!(sim.iter().find(|(u, _)| u.id() == 0).unwrap().1.iter().map(|v| v.id()).collect::<HashSet<_>>(), HashSet::from([10]));
+    assert!(threshold(&scores, 1.0).iter().find(|(u, _)| u.id() == 0).unwrap().1.is_empty());
+}
+
+#[test]
+fn fractional_simulation_crisp() {
+    let mut rng = Pcg64::seed_from_u64(15);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 4, 5, 2);
+        let data = random_graph(&mut rng, 12, 24, 2);
+
+        let options = FractionalOptions { pre_weight: 0.0, epsilon: 0.0, ..Default::default() };
+        let scores = pattern.get_fractional_simulation(&data, options);
+        assert!(threshold(&scores, 1.0 - 1e-9) == pattern.get_simulation_hhk(&data));
+
+        let options = FractionalOptions { epsilon: 0.0, ..Default::default() };
+        let scores = pattern.get_fractional_simulation(&data, options);
+        assert!(threshold(&scores, 1.0 - 1e-9) == pattern.get_dual_simulation(&data));
+    }
+}
+
+#[test]
+fn fractional_simulation_converges() {
+    let mut rng = Pcg64::seed_from_u64(16);
+    let pattern = random_graph(&mut rng, 5, 8, 3);
+    let data = random_graph(&mut rng, 20, 50, 3);
+    let coarse = pattern.get_fractional_simulation(&data, FractionalOptions { epsilon: 1e-3, ..Default::default() });
+    let fine = pattern.get_fractional_simulation(&data, FractionalOptions { epsilon: 1e-12, max_rounds: 1000, ..Default::default() });
+    for (pair, score) in &fine {
+        assert!((0.0..=1.0).contains(score));
+        assert!((coarse[pair] - score).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::hyper::{by_id, LabeledHyperedge, TypedHypergraph};
+use graph_simulation::algorithm::hyper_simulation::{HyperSimulation, HyperSimulationOptions, LMatch};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+/// `l_match(e, e', u)` is every node of `e'` sharing the type of `u`.
+struct TypeLMatch {
+    types: HashMap<usize, usize>,
+    matches: HashMap<(LabeledHyperedge, LabeledHyperedge), HashMap<usize, HashSet<usize>>>,
+    empty: HashSet<usize>,
+}
+
+impl TypeLMatch {
+    fn build(pattern: &TypedHypergraph, data: &TypedHypergraph) -> Self {
+        let mut l_match = <Self as LMatch>::new();
+        l_match.types.extend(data.nodes.iter().map(|node| (node.id, node.node_type)));
+        for e in &pattern.edges {
+            for e_prime in &data.edges {
+                let mapping = e.nodes.iter().map(|u| {
+                    let u_type = pattern.nodes[*u].node_type;
+                    (*u, e_prime.nodes.iter().copied().filter(|v| l_match.types[v] == u_type).collect())
+                }).collect();
+                l_match.matches.insert((e.clone(), e_prime.clone()), mapping);
+            }
+        }
+        l_match
+    }
+}
+
+impl LMatch for TypeLMatch {
+    type Edge = LabeledHyperedge;
+
+    fn new() -> Self {
+        TypeLMatch { types: HashMap::new(), matches: HashMap::new(), empty: HashSet::new() }
+    }
+
+    fn l_match_with_node_mut(&mut self, e: &Self::Edge, e_prime: &Self::Edge, u: usize) -> &HashSet<usize> {
+        self.l_match_with_node(e, e_prime, u)
+    }
+
+    fn l_match_with_node(&self, e: &Self::Edge, e_prime: &Self::Edge, u: usize) -> &HashSet<usize> {
+        self.matches.get(&(e.clone(), e_prime.clone())).and_then(|mapping| mapping.get(&u)).unwrap_or(&self.empty)
+    }
+
+    fn dom(&self, e: &Self::Edge, e_prime: &Self::Edge) -> impl Iterator<Item = &usize> {
+        self.matches.get(&(e.clone(), e_prime.clone())).into_iter().flat_map(|mapping| mapping.keys())
+    }
+}
+
+#[test]
+fn simulation_fixpoint_recursive_same() {
+    let mut rng = Pcg64::seed_from_u64(42);
+    for _ in 0..50 {
+        let pattern = TypedHypergraph::random(&mut rng, 5, 4, 2, 2);
+        let data = TypedHypergraph::random(&mut rng, 12, 10, 2, 2);
+        let mut l_match = TypeLMatch::build(&pattern, &data);
+        let options = HyperSimulationOptions::default();
+
+        let naive = by_id(&pattern.get_simulation_naive(&data, &mut l_match, &options));
+        let fixpoint = by_id(&pattern.get_simulation_fixpoint(&data, &mut l_match, &options));
+        let recursive = by_id(&pattern.get_simulation_recursive(&data, &mut l_match, &options));
+
+        assert_eq!(naive, fixpoint);
+        assert_eq!(naive, recursive);
+    }
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::hyper::{by_id, RandomClusters, TypedHypergraph, TypedNode};
+use graph_simulation::algorithm::hyper_simulation::{read_hc_map, write_hc_map, HyperSimulation, HyperSimulationEngine, HyperSimulationOptions, HyperSimulationTrace};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn hyper_simulation_engine_same() {
+    let options = HyperSimulationOptions::default();
+    let mut rng = Pcg64::seed_from_u64(22);
+    for _ in 0..100 {
+        let pattern = TypedHypergraph::random(&mut rng, 6, 0, 2, 1);
+        let data = TypedHypergraph::random(&mut rng, 12, 0, 2, 1);
+        let clusters = RandomClusters::random(&mut rng, &pattern, &data, 5);
+
+        let naive = by_id(&pattern.get_hyper_simulation_naive(&data, &clusters, &clusters, &options).0);
+        assert_eq!(by_id(&pattern.get_hyper_simulation_effect(&data, &clusters, &clusters, &options).0), naive);
+
+        let type_same_lookup: HashMap<&TypedNode, HashSet<&TypedNode>> = pattern.nodes.iter()
+            .map(|u| (u, data.nodes.iter().filter(|v| v.node_type == u.node_type).collect()))
+            .collect();
+        assert_eq!(by_id(&pattern.get_hyper_simulation_effect_pass_by(&data, &clusters, &clusters, &type_same_lookup, &options).0), naive);
+
+        let hc_map = clusters.hc_map(&pattern, &data);
+        assert_eq!(pattern.get_hyper_simulation_effect_by_id(&hc_map, &options).0, naive);
+
+        let engine: HyperSimulationEngine = hc_map.into_iter().collect();
+        assert_eq!(engine.run(), naive);
+    }
+}
+
+#[test]
+fn hyper_simulation_engine_cascade() {
+    // (0, 0) needs (1, 1) through the cluster pair (0, 0), which needs (2, 2) through (1, 1), which is not a candidate.
+    // (3, 3) falls with (0, 0), whose cluster pair it shares.
+    let mut engine = HyperSimulationEngine::new();
+    engine.insert((0, 0), vec![((0, 0), HashSet::from([(0, 0), (1, 1)]))]);
+    engine.insert((1, 1), vec![((1, 1), HashSet::from([(1, 1), (2, 2)]))]);
+    engine.insert((3, 3), vec![((0, 0), HashSet::new()), ((2, 2), HashSet::from([(3, 3)]))]);
+    engine.insert((4, 4), Vec::new());
+    assert_eq!(engine.len(), 4);
+    assert_eq!(engine.run(), HashSet::from([(4, 4)]));
+
+    // The D-match of a cluster pair is the one it was first inserted with.
+    let mut engine = HyperSimulationEngine::new();
+    engine.extend([((0, 0), vec![((0, 0), HashSet::from([(0, 0)]))]), ((1, 1), vec![((0, 0), HashSet::new())])]);
+    assert_eq!(engine.run(), HashSet::from([(0, 0), (1, 1)]));
+}
+
+#[test]
+fn hyper_simulation_engine_stream() {
+    let options = HyperSimulationOptions::default();
+    let path = std::env::temp_dir().join(format!("hyper_simulation_engine_{}.hc", std::process::id()));
+    let mut rng = Pcg64::seed_from_u64(23);
+    for _ in 0..20 {
+        let pattern = TypedHypergraph::random(&mut rng, 6, 0, 2, 1);
+        let data = TypedHypergraph::random(&mut rng, 12, 0, 2, 1);
+        let clusters = RandomClusters::random(&mut rng, &pattern, &data, 5);
+        let hc_map = clusters.hc_map(&pattern, &data);
+        let expected = pattern.get_hyper_simulation_effect_by_id(&hc_map, &options).0;
+
+        assert_eq!(pattern.get_hyper_simulation_effect_by_id_stream(hc_map.clone(), &options).0, expected);
+
+        write_hc_map(&path, hc_map.clone(), 4).unwrap();
+        let chunks: Vec<_> = read_hc_map(&path).unwrap().map(Result::unwrap).collect();
+        assert_eq!(chunks.len(), hc_map.len().div_ceil(4));
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 4));
+        assert_eq!(chunks.into_iter().flatten().collect::<HashMap<_, _>>(), hc_map);
+        assert_eq!(pattern.get_hyper_simulation_effect_by_id_file(&path, &options).unwrap().0, expected);
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    let pattern = TypedHypergraph::random(&mut rng, 1, 0, 1, 1);
+    assert!(pattern.get_hyper_simulation_effect_by_id_file(std::env::temp_dir().join("hyper_simulation_engine_missing.hc"), &options).is_err());
+}
+
+#[test]
+fn hyper_simulation_engine_options() {
+    let path = std::env::temp_dir().join(format!("hyper_simulation_engine_options_{}.hc", std::process::id()));
+    let trace_file = path.with_extension("trace");
+    let mut rng = Pcg64::seed_from_u64(26);
+    for _ in 0..20 {
+        let pattern = TypedHypergraph::random(&mut rng, 6, 0, 2, 1);
+        let data = TypedHypergraph::random(&mut rng, 12, 0, 2, 1);
+        let clusters = RandomClusters::random(&mut rng, &pattern, &data, 5);
+        // Neither log nor trace by default.
+        let options = HyperSimulationOptions::default();
+        let naive = by_id(&pattern.get_hyper_simulation_naive(&data, &clusters, &clusters, &options).0);
+        let type_same_lookup: HashMap<&TypedNode, HashSet<&TypedNode>> = pattern.nodes.iter()
+            .map(|u| (u, data.nodes.iter().filter(|v| v.node_type == u.node_type).collect()))
+            .collect();
+        let hc_map = clusters.hc_map(&pattern, &data);
+        write_hc_map(&path, hc_map.clone(), 4).unwrap();
+
+        let (sim, trace) = pattern.get_hyper_simulation_effect_pass_by(&data, &clusters, &clusters, &type_same_lookup, &options);
+        assert!(by_id(&sim) == naive && trace.is_none());
+        assert_eq!(pattern.get_hyper_simulation_effect_by_id(&hc_map, &options), (naive.clone(), None));
+        assert_eq!(pattern.get_hyper_simulation_effect_by_id_stream(hc_map.clone(), &options), (naive.clone(), None));
+        assert_eq!(pattern.get_hyper_simulation_effect_by_id_file(&path, &options).unwrap(), (naive.clone(), None));
+
+        // The trace of pass_by starts from the pairs of the same type, the ones by id from the pairs of the hc_map.
+        let options = HyperSimulationOptions { trace: true, trace_file: Some(trace_file.clone()), ..Default::default() };
+        let (sim, trace) = pattern.get_hyper_simulation_effect_pass_by(&data, &clusters, &clusters, &type_same_lookup, &options);
+        let trace = trace.unwrap();
+        assert_eq!(by_id(&sim), naive);
+        assert_eq!(trace.simulation(), naive);
+        assert_eq!(trace.candidates().len(), type_same_lookup.values().map(HashSet::len).sum::<usize>());
+        assert_eq!(HyperSimulationTrace::load(&trace_file).unwrap(), trace);
+
+        let candidates: HashSet<(usize, usize)> = hc_map.keys().copied().collect();
+        let traced = [
+            pattern.get_hyper_simulation_effect_by_id(&hc_map, &options),
+            pattern.get_hyper_simulation_effect_by_id_stream(hc_map.clone(), &options),
+            pattern.get_hyper_simulation_effect_by_id_file(&path, &options).unwrap(),
+        ];
+        for (sim, trace) in traced {
+            let trace = trace.unwrap();
+            assert_eq!(sim, naive);
+            assert_eq!(trace.simulation(), naive);
+            assert_eq!(trace.candidates(), &candidates);
+            assert_eq!(trace.len(), candidates.len() - naive.len());
+        }
+    }
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&trace_file).unwrap();
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::hyper::{by_id, Pair, RandomClusters, TypedHypergraph, TypedNode};
+use graph_base::interfaces::hypergraph::Hypergraph;
+use graph_simulation::algorithm::hyper_simulation::{HSEvent, HyperSimulation, HyperSimulationOptions, HyperSimulationTrace, WhyNot};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn hyper_simulation_options() {
+    let mut rng = Pcg64::seed_from_u64(24);
+    let dir = std::env::temp_dir().join(format!("hyper_simulation_trace_{}", std::process::id()));
+    for round in 0..20 {
+        let pattern = TypedHypergraph::random(&mut rng, 6, 0, 2, 1);
+        let data = TypedHypergraph::random(&mut rng, 12, 0, 2, 1);
+        let clusters = RandomClusters::random(&mut rng, &pattern, &data, 5);
+
+        // Neither log nor trace by default.
+        let options = HyperSimulationOptions::default();
+        let (sim, trace) = pattern.get_hyper_simulation_naive(&data, &clusters, &clusters, &options);
+        let naive = by_id(&sim);
+        assert!(trace.is_none());
+        let (sim, trace) = pattern.get_hyper_simulation_strict(&data, &clusters, &clusters, &options);
+        assert!(trace.is_none() && by_id(&sim).is_subset(&naive));
+        let (sim, trace) = pattern.get_hyper_simulation_effect(&data, &clusters, &clusters, &options);
+        assert!(by_id(&sim) == naive && trace.is_none());
+
+        // The trace in memory, and in a file whose directory does not exist yet.
+        let trace_file = dir.join(round.to_string()).join("naive.trace");
+        let options = HyperSimulationOptions { trace: true, trace_file: Some(trace_file.clone()), ..Default::default() };
+        let (sim, trace) = pattern.get_hyper_simulation_naive(&data, &clusters, &clusters, &options);
+        let trace = trace.unwrap();
+        assert_eq!(by_id(&sim), naive);
+        assert_eq!(HyperSimulationTrace::load(&trace_file).unwrap(), trace);
+        // Every pair of the same type that is not kept was deleted by an event.
+        let same_type = pattern.nodes.iter().flat_map(|u| data.nodes.iter().filter(move |v| v.node_type == u.node_type)).count();
+        assert!(trace.len() >= same_type - naive.len());
+        assert!(trace.events().iter().all(|event| matches!(event, HSEvent::Base { .. } | HSEvent::Derivation { .. })));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // A trace file that cannot be written does not fail the simulation.
+    let pattern = TypedHypergraph::random(&mut rng, 6, 0, 2, 1);
+    let data = TypedHypergraph::random(&mut rng, 12, 0, 2, 1);
+    let clusters = RandomClusters::random(&mut rng, &pattern, &data, 5);
+    let options = HyperSimulationOptions { trace: true, trace_file: Some("/dev/null/hyper_simulation.trace".into()), ..Default::default() };
+    let (sim, trace) = pattern.get_hyper_simulation_strict(&data, &clusters, &clusters, &options);
+    assert_eq!(by_id(&sim), by_id(&pattern.get_hyper_simulation_strict(&data, &clusters, &clusters, &HyperSimulationOptions::default()).0));
+    assert!(trace.is_some());
+}
+
+/// Checks that `why` is a derivation of its pair not being in the hyper simulation of `trace`, and returns its size.
+fn check_why_not(trace: &HyperSimulationTrace, clusters: &RandomClusters, why: &WhyNot) -> usize {
+    assert!(!trace.simulation().contains(&why.pair));
+    let Some(event) = why.event else {
+        assert!(!trace.candidates().contains(&why.pair) && why.premises.is_empty());
+        return 1;
+    };
+    let index = trace.removal(why.pair).unwrap();
+    assert!(std::ptr::eq(event, &trace.events()[index]) && event.pair() == why.pair);
+    let (u, v) = why.pair;
+    let has_cluster = |c_pair: (usize, usize)| clusters.clusters.get(&(u, v)).is_some_and(|list| list.iter().any(|(cu, cv)| (cu.id(), cv.id()) == c_pair));
+    match event {
+        HSEvent::Base { cluster_pair, d_match, .. } => {
+            assert!(has_cluster(*cluster_pair) && !d_match.contains(&why.pair) && why.premises.is_empty());
+            assert_eq!(d_match, &clusters.d_match[cluster_pair]);
+            1
+        }
+        HSEvent::Unclustered { .. } => {
+            assert!(clusters.clusters.get(&(u, v)).is_none_or(Vec::is_empty) && why.premises.is_empty());
+            1
+        }
+        HSEvent::Derivation { round, cluster_pair, uncovered, .. } => {
+            assert!(*round > 0 && has_cluster(*cluster_pair) && !uncovered.is_empty());
+            assert!(uncovered.is_subset(&clusters.d_match[cluster_pair]));
+            // The missing pairs were already out of the simulation when the pair was removed.
+            let before = if index == 0 { trace.candidates().clone() } else { trace.state_after(index - 1) };
+            assert!(uncovered.is_disjoint(&before));
+            let premises: HashSet<(usize, usize)> = why.premises.iter().map(|premise| premise.pair).collect();
+            assert!(premises.is_empty() || &premises == uncovered);
+            1 + why.premises.iter().map(|premise| {
+                assert!(premise.event.is_none_or(|_| trace.removal(premise.pair).unwrap() < index));
+                check_why_not(trace, clusters, premise)
+            }).sum::<usize>()
+        }
+    }
+}
+
+#[test]
+fn hyper_simulation_trace_replay() {
+    let mut rng = Pcg64::seed_from_u64(25);
+    let options = HyperSimulationOptions { trace: true, ..Default::default() };
+    let mut derived = 0;
+    for _ in 0..50 {
+        let pattern = TypedHypergraph::random(&mut rng, 6, 0, 2, 1);
+        let data = TypedHypergraph::random(&mut rng, 12, 0, 2, 1);
+        let clusters = RandomClusters::random(&mut rng, &pattern, &data, 5);
+
+        for method in 0..3 {
+            let (sim, trace) = match method {
+                0 => pattern.get_hyper_simulation_naive(&data, &clusters, &clusters, &options),
+                1 => pattern.get_hyper_simulation_strict(&data, &clusters, &clusters, &options),
+                _ => pattern.get_hyper_simulation_effect(&data, &clusters, &clusters, &options),
+            };
+            let (sim, trace) = (by_id(&sim), trace.unwrap());
+            assert_eq!(trace.simulation(), sim);
+
+            // Every event removes a pair still in the simulation, in rounds that never go back.
+            let mut replay = trace.replay();
+            let mut round = 0;
+            while let Some(event) = replay.step() {
+                assert!(event.round() >= round);
+                round = event.round();
+                assert!(!replay.state().contains(&event.pair()));
+                assert_eq!(replay.state(), &trace.state_after(replay.position() - 1));
+            }
+            assert_eq!(replay.state(), &sim);
+            assert_eq!(replay.position(), trace.len());
+            assert_eq!(trace.candidates().len() - sim.len(), trace.len());
+
+            for u in &pattern.nodes {
+                for v in &data.nodes {
+                    let pair = (u.id, v.id);
+                    match trace.why_not(pair) {
+                        None => assert!(sim.contains(&pair)),
+                        Some(why) => {
+                            assert_eq!(why.pair, pair);
+                            if check_why_not(&trace, &clusters, &why) > 2 {
+                                derived += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    assert!(derived > 0);
+}
+
+#[test]
+fn hyper_simulation_trace_example() {
+    // (0, 0) needs (1, 1), which needs (2, 2), which is not in its own D-match. The data node 3 has another type.
+    let mut pattern = <TypedHypergraph as Hypergraph>::new();
+    let mut data = <TypedHypergraph as Hypergraph>::new();
+    for id in 0..3 {
+        pattern.add_node(TypedNode { id, node_type: 0 });
+        data.add_node(TypedNode { id, node_type: 0 });
+    }
+    data.add_node(TypedNode { id: 3, node_type: 1 });
+    let clusters = RandomClusters::build(
+        &[((0, 0), (0, 0)), ((1, 1), (1, 1)), ((2, 2), (2, 2))],
+        &[((0, 0), &[(0, 0), (1, 1)]), ((1, 1), &[(1, 1), (2, 2)]), ((2, 2), &[])],
+    );
+
+    let options = HyperSimulationOptions { trace: true, ..Default::default() };
+    let (sim, trace) = pattern.get_hyper_simulation_naive(&data, &clusters, &clusters, &options);
+    let trace = trace.unwrap();
+    assert_eq!(by_id(&sim).len(), 6);
+    let removed: Vec<(Pair, usize, Option<Pair>)> = trace.events().iter().map(|event| (event.pair(), event.round(), event.cluster_pair())).collect();
+    assert_eq!(removed, vec![((2, 2), 0, Some((2, 2))), ((1, 1), 1, Some((1, 1))), ((0, 0), 2, Some((0, 0)))]);
+    assert!(trace.state_after(0).contains(&(1, 1)) && !trace.state_after(1).contains(&(1, 1)));
+
+    let why = trace.why_not((0, 0)).unwrap();
+    assert!(matches!(why.event, Some(HSEvent::Derivation { round: 2, .. })));
+    let why = &why.premises[..];
+    assert!(why.len() == 1 && why[0].pair == (1, 1));
+    let why = &why[0].premises[..];
+    assert!(why.len() == 1 && why[0].pair == (2, 2) && why[0].premises.is_empty());
+    assert!(matches!(why[0].event, Some(HSEvent::Base { cluster_pair: (2, 2), .. })));
+
+    assert!(trace.why_not((0, 1)).is_none());
+    let why = trace.why_not((0, 3)).unwrap();
+    assert!(why.event.is_none() && why.premises.is_empty());
+
+    // In the strict simulation, the pairs without clusters go first.
+    let (sim, trace) = pattern.get_hyper_simulation_strict(&data, &clusters, &clusters, &options);
+    let trace = trace.unwrap();
+    assert!(by_id(&sim).is_empty());
+    assert!(matches!(trace.why_not((0, 1)).unwrap().event, Some(HSEvent::Unclustered { pair: (0, 1) })));
+    assert_eq!(trace.why_not((0, 0)).unwrap().premises[0].pair, (1, 1));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::build_graph;
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::incremental::{IncrementalSimulation, SimulationDelta};
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+fn pairs<N: SingleId>(sim: &HashMap<&N, HashSet<&N>>) -> HashSet<(usize, usize)> {
+    sim.iter().flat_map(|(u, sim_u)| sim_u.iter().map(|v| (u.id(), v.id()))).collect()
+}
+
+fn expected_pairs(pattern: &StandardLabeledGraph, nodes: &[(u64, String)], edges: &[(u64, u64)]) -> HashSet<(usize, usize)> {
+    let data = build_graph(nodes, edges);
+    pairs(&pattern.get_simulation_hhk(&data))
+}
+
+fn check_delta(before: &HashSet<(usize, usize)>, after: &HashSet<(usize, usize)>, delta: &SimulationDelta) {
+    assert_eq!(delta.added, after.difference(before).copied().collect());
+    assert_eq!(delta.removed, before.difference(after).copied().collect());
+}
+
+#[test]
+fn incremental_simulation_same() {
+    let mut rng = Pcg64::seed_from_u64(3);
+    for _ in 0..30 {
+        let pattern_nodes: Vec<(u64, String)> = (0..4).map(|u| (u, rng.random_range(0..2).to_string())).collect();
+        let pattern_edges: Vec<(u64, u64)> = (0..4).map(|_| (rng.random_range(0..4), rng.random_range(0..4))).collect();
+        let pattern = build_graph(&pattern_nodes, &pattern_edges);
+
+        let mut nodes: Vec<(u64, String)> = (0..12).map(|v| (v, rng.random_range(0..2).to_string())).collect();
+        let mut edges: Vec<(u64, u64)> = (0..18).map(|_| (rng.random_range(0..12), rng.random_range(0..12))).collect();
+        let mut next_node = 12;
+
+        let data = build_graph(&nodes, &edges);
+        let mut incremental = IncrementalSimulation::new(&pattern, &data);
+        let mut before = expected_pairs(&pattern, &nodes, &edges);
+        assert_eq!(pairs(&incremental.relation()), before);
+
+        for _ in 0..60 {
+            let delta = match rng.random_range(0..10) {
+                0 => {
+                    let label = rng.random_range(0..2).to_string();
+                    let node = build_graph(&[(next_node, label.clone())], &[]).nodes().next().unwrap().clone();
+                    nodes.push((next_node, label));
+                    next_node += 1;
+                    incremental.insert_node(node)
+                }
+                1 if nodes.len() > 2 => {
+                    let (x, _) = nodes.swap_remove(rng.random_range(0..nodes.len()));
+                    edges.retain(|&(source, destination)| source != x && destination != x);
+                    incremental.delete_node(x as usize)
+                }
+                2..=5 => {
+                    let x = nodes[rng.random_range(0..nodes.len())].0;
+                    let y = nodes[rng.random_range(0..nodes.len())].0;
+                    edges.push((x, y));
+                    incremental.insert_edge(x as usize, y as usize)
+                }
+                _ if !edges.is_empty() => {
+                    let (x, y) = edges.swap_remove(rng.random_range(0..edges.len()));
+                    incremental.delete_edge(x as usize, y as usize)
+                }
+                _ => continue,
+            };
+
+            let after = expected_pairs(&pattern, &nodes, &edges);
+            assert_eq!(pairs(&incremental.relation()), after);
+            check_delta(&before, &after, &delta);
+            before = after;
+        }
+    }
+}
+
+#[test]
+fn incremental_simulation_absent() {
+    let nodes = [(0, "a".to_string()), (1, "b".to_string())];
+    let pattern = build_graph(&nodes, &[(0, 1)]);
+    let data = build_graph(&nodes, &[(0, 1)]);
+    let mut incremental = IncrementalSimulation::new(&pattern, &data);
+    let before = pairs(&incremental.relation());
+
+    assert!(incremental.insert_edge(0, 7).is_empty());
+    assert!(incremental.insert_edge(7, 1).is_empty());
+    assert!(incremental.delete_edge(1, 0).is_empty());
+    assert!(incremental.delete_node(7).is_empty());
+    assert_eq!(pairs(&incremental.relation()), before);
+    assert_eq!(pairs(&incremental.relation()), expected_pairs(&pattern, &nodes, &[(0, 1)]));
+}
@@ -49,7 +49,7 @@ fn simulation_inter_test() {
                     }
 
                     let sim = graph1.get_simulation_inter(&graph2);
-                    let has_sim=  StandardLabeledGraph::has_simulation(sim);
+                    let has_sim=  StandardLabeledGraph::has_simulation(&sim);
             }
         }
     }
@@ -0,0 +1,76 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::{build_graph, random_graph};
+use graph_base::impls::standard::LabelNode;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::match_graph::ResultGraph;
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn match_graph_example() {
+    // A -> B, and an isolated C.
+    let pattern = build_graph(&[(0, "A"), (1, "B"), (2, "C")], &[(0, 1)]);
+    let data = build_graph(
+        &[(10, "A"), (11, "B"), (12, "A"), (13, "B"), (14, "B"), (15, "C"), (16, "A")],
+        &[(10, 11), (12, 13), (12, 14), (11, 12), (16, 10)],
+    );
+    let sim = pattern.get_simulation_hhk(&data);
+    let graph = pattern.get_match_graph(&data, &sim);
+
+    assert_eq!(graph.nodes().map(|v| v.id()).collect::<Vec<_>>(), vec![10, 11, 12, 13, 14, 15]);
+    let edges: Vec<(usize, usize)> = graph.edges().map(|(v, w)| (v.id(), w.id())).collect();
+    assert_eq!(edges, vec![(10, 11), (12, 13), (12, 14)]);
+    let node = |id| data.nodes().find(|v| v.id() == id).unwrap();
+    assert_eq!(graph.pattern_nodes(node(15)).iter().map(|u| u.id()).collect::<Vec<_>>(), vec![2]);
+    assert_eq!(graph.pattern_nodes(node(12)).iter().map(|u| u.id()).collect::<Vec<_>>(), vec![0]);
+    assert!(graph.pattern_nodes(node(16)).is_empty());
+    assert!(!graph.contains_edge(node(11), node(12)));
+    assert_eq!(graph.pattern_edges(node(10), node(11)).iter().map(|(u, w)| (u.id(), w.id())).collect::<Vec<_>>(), vec![(0, 1)]);
+
+    let components: Vec<Vec<usize>> = graph.components().iter().map(|component| component.nodes().map(|v| v.id()).collect()).collect();
+    assert_eq!(components, vec![vec![10, 11], vec![12, 13, 14], vec![15]]);
+}
+
+#[test]
+fn match_graph_edges() {
+    let mut rng = Pcg64::seed_from_u64(17);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 4, 5, 3);
+        let data = random_graph(&mut rng, 16, 40, 3);
+        let sim = pattern.get_simulation_hhk(&data);
+        let graph = pattern.get_match_graph(&data, &sim);
+
+        let pattern_edges: HashSet<(&LabelNode<String>, &LabelNode<String>)> = pattern.get_edges_pair().collect();
+        let expected: HashSet<_> = data.get_edges_pair().filter(|&(v, w)| {
+            pattern_edges.iter().any(|(u, x)| sim[u].contains(v) && sim[x].contains(w))
+        }).collect();
+        assert!(graph.edges().collect::<HashSet<_>>() == expected);
+        for (v, w) in graph.edges() {
+            assert!(graph.contains_node(v) && graph.contains_node(w));
+            for &(u, x) in graph.pattern_edges(v, w) {
+                assert!(graph.pattern_nodes(v).contains(&u) && graph.pattern_nodes(w).contains(&x));
+            }
+        }
+
+        // The components split the nodes and the edges, and no edge joins two of them.
+        let components = graph.components();
+        let mut seen: HashMap<&LabelNode<String>, usize> = HashMap::new();
+        for (c, component) in components.iter().enumerate() {
+            assert!(!component.is_empty());
+            for v in component.nodes() {
+                assert!(seen.insert(v, c).is_none());
+            }
+        }
+        assert_eq!(seen.len(), graph.len());
+        for (c, component) in components.iter().enumerate() {
+            for (v, w) in component.edges() {
+                assert!(seen[v] == c && seen[w] == c);
+            }
+        }
+        assert_eq!(components.iter().map(|component| component.edges().count()).sum::<usize>(), graph.edges().count());
+    }
+}
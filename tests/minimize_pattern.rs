@@ -0,0 +1,66 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::{build_graph, ids, random_graph};
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::minimize::{MinimizePattern, SimulationEquivalence};
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+fn edges(graph: &StandardLabeledGraph) -> HashSet<(usize, usize)> {
+    graph.get_edges_pair().map(|(u, v)| (u.id(), v.id())).collect()
+}
+
+#[test]
+fn minimize_pattern_example() {
+    // Two equivalent B leaves under A, and a B leaf under A that the B with a C child simulates.
+    let pattern = build_graph(
+        &[(0, "A"), (1, "B"), (2, "B"), (3, "A"), (4, "B"), (5, "B"), (6, "C")],
+        &[(0, 1), (0, 2), (3, 4), (3, 5), (5, 6)],
+    );
+    let classes: Vec<Vec<usize>> = pattern.get_simulation_classes().iter().map(|class| class.iter().map(|u| u.id()).collect()).collect();
+    assert_eq!(classes, vec![vec![0], vec![1, 2, 4], vec![3], vec![5], vec![6]]);
+
+    let minimized = pattern.minimize_pattern();
+    assert_eq!(minimized.nodes().map(|u| u.id()).collect::<Vec<_>>(), vec![0, 1, 3, 5, 6]);
+    assert_eq!(edges(&minimized), HashSet::from([(0, 1), (3, 5), (5, 6)]));
+}
+
+#[test]
+fn minimize_pattern_same_matches() {
+    let mut rng = Pcg64::seed_from_u64(18);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 7, 9, 2);
+        let minimized = pattern.minimize_pattern();
+        let representative: HashMap<usize, usize> = pattern.get_simulation_classes().iter()
+            .flat_map(|class| class.iter().map(|u| (u.id(), class[0].id())).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(minimized.nodes().count(), representative.values().collect::<HashSet<_>>().len());
+        assert!(edges(&minimized.minimize_pattern()) == edges(&minimized));
+
+        for data in [random_graph(&mut rng, 12, 24, 2), pattern.minimize_pattern()] {
+            let (expected, actual) = (ids(&pattern.get_simulation_hhk(&data)), ids(&minimized.get_simulation_hhk(&data)));
+            for u in pattern.nodes() {
+                assert!(expected[&u.id()] == actual[&representative[&u.id()]]);
+            }
+        }
+    }
+}
+
+#[test]
+fn self_simulation() {
+    let mut rng = Pcg64::seed_from_u64(19);
+    for _ in 0..50 {
+        let graph = random_graph(&mut rng, 8, 12, 2);
+        let sim = graph.get_simulation();
+        assert!(graph.nodes().all(|u| sim[u].contains(u)));
+        for (u, u_prime) in graph.get_edges_pair() {
+            for &w in &sim[u] {
+                assert!(graph.get_edges_pair().any(|(x, w_prime)| x == w && sim[u_prime].contains(w_prime)));
+            }
+        }
+    }
+}
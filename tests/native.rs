@@ -49,7 +49,7 @@ fn simulation_native_test() {
                     }
 
                     let sim = graph1.get_simulation_native(&graph2);
-                    let has_sim=  StandardLabeledGraph::has_simulation(sim);
+                    let has_sim=  StandardLabeledGraph::has_simulation(&sim);
             }
         }
     }
@@ -0,0 +1,213 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::ids;
+use graph_base::impls::standard::{LabelNode, StandardLabeledGraph};
+use graph_base::interfaces::graph::{Adjacency, AdjacencyInv, Directed, Graph, IdPair, SingleId};
+use graph_base::interfaces::labeled::{Label, Labeled, LabeledAdjacency};
+use graph_simulation::algorithm::bounded::{Bound, Bounded};
+use graph_simulation::algorithm::path::{PathLabels, PathSimulation};
+use graph_simulation::algorithm::simulation::Simulation;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Edge {
+    src: u64,
+    dst: u64,
+    label: String,
+}
+
+impl IdPair for Edge {
+    fn pair(&self) -> (usize, usize) {
+        (self.src as usize, self.dst as usize)
+    }
+}
+
+/// A graph with labeled nodes and labeled edges, whose edges carry a bound, at most 1 unless set otherwise.
+struct PathGraph {
+    graph: StandardLabeledGraph,
+    edges: Vec<Edge>,
+    bounds: HashMap<(usize, usize), Bound>,
+}
+
+impl PathGraph {
+    fn build(nodes: &[(u64, &str)], edges: &[(u64, u64, &str, Bound)]) -> Self {
+        let mut graph = <StandardLabeledGraph as Graph>::new();
+        for (node, label) in nodes {
+            graph.add_node(*node, label.to_string());
+        }
+        let bounds = edges.iter().map(|&(src, dst, _, bound)| ((src as usize, dst as usize), bound)).collect();
+        let edges = edges.iter().map(|&(src, dst, label, _)| Edge { src, dst, label: label.to_string() }).collect();
+        PathGraph { graph, edges, bounds }
+    }
+}
+
+impl<'a> Graph<'a> for PathGraph {
+    type Node = LabelNode<String>;
+    type Edge = Edge;
+
+    fn new() -> Self {
+        PathGraph { graph: <StandardLabeledGraph as Graph>::new(), edges: Vec::new(), bounds: HashMap::new() }
+    }
+
+    fn nodes(&'a self) -> impl Iterator<Item = &'a Self::Node> {
+        self.graph.nodes()
+    }
+
+    fn edges(&'a self) -> impl Iterator<Item = &'a Self::Edge> {
+        self.edges.iter()
+    }
+
+    fn add_node(&mut self, node: Self::Node) {
+        Graph::add_node(&mut self.graph, node);
+    }
+
+    fn add_edge(&mut self, edge: Self::Edge) {
+        self.edges.push(edge);
+    }
+}
+
+impl<'a> Labeled<'a> for PathGraph {
+    fn label_same(&self, node: &Self::Node, label: &Self::Node) -> bool {
+        self.graph.label_same(node, label)
+    }
+
+    fn get_label(&'a self, node: &'a Self::Node) -> &'a impl Label {
+        self.graph.get_label(node)
+    }
+
+    fn get_edges_pair_label(&'a self) -> impl Iterator<Item = (&'a Self::Node, &'a Self::Node, &'a impl Label)> {
+        self.get_edges_pair_with_edge().map(|(src, edge, dst)| (src, dst, &edge.label))
+    }
+
+    fn edge_label_same(&self, edge1: &Self::Edge, edge2: &Self::Edge) -> bool {
+        edge1.label == edge2.label
+    }
+
+    fn edge_node_label_same(&self, src1: &Self::Node, edge1: &Self::Edge, dst1: &Self::Node, src2: &Self::Node, edge2: &Self::Edge, dst2: &Self::Node) -> bool {
+        self.label_same(src1, src2) && self.edge_label_same(edge1, edge2) && self.label_same(dst1, dst2)
+    }
+}
+
+impl Directed for PathGraph {}
+
+impl Adjacency<'_> for PathGraph {}
+
+impl AdjacencyInv<'_> for PathGraph {}
+
+impl LabeledAdjacency<'_> for PathGraph {}
+
+impl<'a> Bounded<'a> for PathGraph {
+    fn get_bound(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> usize {
+        self.get_bound_range(u, v).limits().1.unwrap_or(usize::MAX)
+    }
+
+    fn get_bound_range(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> Bound {
+        self.bounds.get(&(u.id(), v.id())).copied().unwrap_or(Bound::AtMost(1))
+    }
+}
+
+/// The ends of the paths from `v` that match a pattern edge labeled `label`, by enumerating the paths.
+fn naive_ends(data: &PathGraph, v: usize, bound: Bound, labels: PathLabels, label: &str) -> HashSet<usize> {
+    let (lo, hi) = bound.limits();
+    let mut ends = HashSet::new();
+    let mut paths: Vec<(usize, Vec<bool>)> = vec![(v, Vec::new())];
+    for length in 1..=hi.unwrap() {
+        let mut longer = Vec::new();
+        for (end, matches) in paths {
+            for edge in data.edges.iter().filter(|edge| edge.src as usize == end) {
+                let mut matches = matches.clone();
+                matches.push(edge.label == label);
+                let accepted = match labels {
+                    PathLabels::All => matches.iter().all(|&m| m),
+                    PathLabels::First => matches[0],
+                    PathLabels::Last => matches[matches.len() - 1],
+                    PathLabels::Any => matches.iter().any(|&m| m),
+                };
+                if accepted && length >= lo {
+                    ends.insert(edge.dst as usize);
+                }
+                longer.push((edge.dst as usize, matches));
+            }
+        }
+        paths = longer;
+    }
+    ends
+}
+
+fn naive_path_simulation(pattern: &PathGraph, data: &PathGraph, labels: PathLabels) -> HashMap<usize, HashSet<usize>> {
+    let ends: Vec<(usize, usize, HashMap<usize, HashSet<usize>>)> = pattern.edges.iter().map(|edge| {
+        let bound = pattern.bounds[&(edge.src as usize, edge.dst as usize)];
+        let ends = data.nodes().map(|v| (v.id(), naive_ends(data, v.id(), bound, labels, &edge.label))).collect();
+        (edge.src as usize, edge.dst as usize, ends)
+    }).collect();
+    let mut sim: HashMap<usize, HashSet<usize>> = pattern.nodes().map(|u| {
+        (u.id(), data.nodes().filter(|v| pattern.label_same(u, v)).map(|v| v.id()).collect())
+    }).collect();
+    loop {
+        let mut changed = false;
+        for (u, u_prime, ends) in &ends {
+            let sim_u_prime = sim[u_prime].clone();
+            let before = sim[u].len();
+            sim.get_mut(u).unwrap().retain(|v| ends[v].iter().any(|v_prime| sim_u_prime.contains(v_prime)));
+            changed |= sim[u].len() != before;
+        }
+        if !changed {
+            return sim;
+        }
+    }
+}
+
+fn random_graph(rng: &mut impl Rng, n: u64, m: usize, edge_labels: &[&'static str], max_bound: usize) -> PathGraph {
+    let nodes: Vec<(u64, &str)> = (0..n).map(|node| (node, ["A", "B"][rng.random_range(0..2)])).collect();
+    let edges: Vec<(u64, u64, &str, Bound)> = (0..m).map(|_| {
+        let (lo, hi) = (rng.random_range(0..=max_bound), rng.random_range(1..=max_bound));
+        let bound = match rng.random_range(0..3) {
+            0 => Bound::AtMost(hi),
+            1 => Bound::Exactly(hi),
+            _ => Bound::Range(lo.min(hi), hi),
+        };
+        (rng.random_range(0..n), rng.random_range(0..n), edge_labels[rng.random_range(0..edge_labels.len())], bound)
+    }).collect();
+    PathGraph::build(&nodes, &edges)
+}
+
+#[test]
+fn path_simulation_same() {
+    let mut rng = Pcg64::seed_from_u64(12);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 3, 3, &["x", "y"], 3);
+        let data = random_graph(&mut rng, 8, 14, &["x", "y", "z"], 1);
+        for labels in [PathLabels::All, PathLabels::First, PathLabels::Last, PathLabels::Any] {
+            assert_eq!(ids(&pattern.get_simulation_of_node_path(&data, labels)), naive_path_simulation(&pattern, &data, labels));
+        }
+    }
+}
+
+#[test]
+fn path_simulation_of_single_edges() {
+    let mut rng = Pcg64::seed_from_u64(13);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 4, 5, &["x", "y"], 1);
+        let pattern = PathGraph { bounds: HashMap::new(), ..pattern };
+        let data = random_graph(&mut rng, 12, 24, &["x", "y", "z"], 1);
+        assert_eq!(ids(&pattern.get_simulation_of_node_path(&data, PathLabels::All)), ids(&pattern.get_simulation_of_node_edge(&data)));
+    }
+}
+
+#[test]
+fn path_simulation_example() {
+    // knows(P) -[friend{1,2}, last hop friend]-> C
+    let pattern = PathGraph::build(&[(0, "P"), (1, "C")], &[(0, 1, "friend", Bound::AtMost(2))]);
+    let data = PathGraph::build(
+        &[(10, "P"), (11, "P"), (12, "C"), (13, "P"), (14, "C")],
+        &[(10, 11, "colleague", Bound::AtMost(1)), (11, 12, "friend", Bound::AtMost(1)), (13, 14, "colleague", Bound::AtMost(1))],
+    );
+    let matches = |labels| ids(&pattern.get_simulation_of_node_path(&data, labels))[&0].clone();
+    assert_eq!(matches(PathLabels::All), HashSet::from([11]));
+    assert_eq!(matches(PathLabels::Last), HashSet::from([10, 11]));
+    assert_eq!(matches(PathLabels::First), HashSet::from([11]));
+    assert_eq!(matches(PathLabels::Any), HashSet::from([10, 11]));
+}
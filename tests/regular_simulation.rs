@@ -0,0 +1,195 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::ids;
+use graph_base::impls::standard::{LabelNode, LabeledEdge, SingleLabel, StandardLabeledGraph};
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_base::interfaces::labeled::{Label, Labeled};
+use graph_simulation::algorithm::regular::{Regular, RegularSimulation};
+use graph_simulation::utils::automaton::EdgeRegex;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// A `StandardLabeledGraph` whose edges carry a label: an edge label in a data graph, a regular expression in a pattern.
+struct RegularGraph {
+    graph: StandardLabeledGraph,
+    labels: HashMap<(usize, usize), String>,
+}
+
+impl RegularGraph {
+    fn build(nodes: &[(u64, &str)], edges: &[(u64, u64, &str)]) -> Self {
+        let mut graph = <StandardLabeledGraph as Graph>::new();
+        for (node, label) in nodes {
+            graph.add_node(*node, label.to_string());
+        }
+        let mut labels = HashMap::new();
+        for &(source, destination, label) in edges {
+            if labels.insert((source as usize, destination as usize), label.to_string()).is_none() {
+                graph.add_edge(source, destination);
+            }
+        }
+        RegularGraph { graph, labels }
+    }
+}
+
+impl<'a> Graph<'a> for RegularGraph {
+    type Node = LabelNode<String>;
+    type Edge = LabeledEdge<SingleLabel>;
+
+    fn new() -> Self {
+        RegularGraph { graph: <StandardLabeledGraph as Graph>::new(), labels: HashMap::new() }
+    }
+
+    fn nodes(&'a self) -> impl Iterator<Item = &'a Self::Node> {
+        self.graph.nodes()
+    }
+
+    fn edges(&'a self) -> impl Iterator<Item = &'a Self::Edge> {
+        self.graph.edges()
+    }
+
+    fn add_node(&mut self, node: Self::Node) {
+        Graph::add_node(&mut self.graph, node);
+    }
+
+    fn add_edge(&mut self, edge: Self::Edge) {
+        Graph::add_edge(&mut self.graph, edge);
+    }
+}
+
+impl<'a> Labeled<'a> for RegularGraph {
+    fn label_same(&self, node: &Self::Node, label: &Self::Node) -> bool {
+        self.graph.label_same(node, label)
+    }
+
+    fn get_label(&'a self, node: &'a Self::Node) -> &'a impl Label {
+        self.graph.get_label(node)
+    }
+
+    fn get_edges_pair_label(&'a self) -> impl Iterator<Item = (&'a Self::Node, &'a Self::Node, &'a impl Label)> {
+        self.graph.get_edges_pair().map(|(u, v)| (u, v, &self.labels[&(u.id(), v.id())]))
+    }
+
+    fn edge_label_same(&self, edge1: &Self::Edge, edge2: &Self::Edge) -> bool {
+        self.graph.edge_label_same(edge1, edge2)
+    }
+
+    fn edge_node_label_same(&self, src1: &Self::Node, edge1: &Self::Edge, dst1: &Self::Node, src2: &Self::Node, edge2: &Self::Edge, dst2: &Self::Node) -> bool {
+        self.graph.edge_node_label_same(src1, edge1, dst1, src2, edge2, dst2)
+    }
+}
+
+impl<'a> Regular<'a> for RegularGraph {
+    fn get_regex(&'a self, u: &'a Self::Node, v: &'a Self::Node) -> &'a str {
+        &self.labels[&(u.id(), v.id())]
+    }
+}
+
+/// The pairs (v, v') joined by a path of 1 to `max_len` edges whose labels match `regex`, by enumerating the paths.
+fn naive_reach(data: &RegularGraph, regex: &EdgeRegex, max_len: usize) -> HashSet<(usize, usize)> {
+    let mut reach = HashSet::new();
+    let mut paths: Vec<(usize, usize, Vec<&str>)> = data.nodes().map(|v| (v.id(), v.id(), Vec::new())).collect();
+    for _ in 0..max_len {
+        let mut longer = Vec::new();
+        for (start, end, labels) in paths {
+            for (&(_, y), label) in data.labels.iter().filter(|((x, _), _)| *x == end) {
+                let mut labels = labels.clone();
+                labels.push(label.as_str());
+                if regex.matches(labels.iter().copied()) {
+                    reach.insert((start, y));
+                }
+                longer.push((start, y, labels));
+            }
+        }
+        paths = longer;
+    }
+    reach
+}
+
+fn naive_regular_simulation(pattern: &RegularGraph, data: &RegularGraph, max_len: usize) -> HashMap<usize, HashSet<usize>> {
+    let reach: HashMap<(usize, usize), HashSet<(usize, usize)>> = pattern.labels.iter().map(|(&edge, regex)| {
+        (edge, naive_reach(data, &EdgeRegex::parse(regex).unwrap(), max_len))
+    }).collect();
+    let mut sim: HashMap<usize, HashSet<usize>> = pattern.nodes().map(|u| {
+        (u.id(), data.nodes().filter(|v| pattern.label_same(u, v)).map(|v| v.id()).collect())
+    }).collect();
+    loop {
+        let mut changed = false;
+        for (&(u, u_prime), reach_e) in &reach {
+            let sim_u_prime = sim[&u_prime].clone();
+            let before = sim[&u].len();
+            sim.get_mut(&u).unwrap().retain(|&v| sim_u_prime.iter().any(|&v_prime| reach_e.contains(&(v, v_prime))));
+            changed |= sim[&u].len() != before;
+        }
+        if !changed {
+            return sim;
+        }
+    }
+}
+
+#[test]
+fn edge_regex_matches() {
+    let regex = EdgeRegex::parse("friend{1,3}·colleague").unwrap();
+    assert!(regex.matches(["friend", "colleague"]));
+    assert!(regex.matches(["friend", "friend", "friend", "colleague"]));
+    assert!(!regex.matches(["friend", "friend", "friend", "friend", "colleague"]));
+    assert!(!regex.matches(["colleague"]));
+
+    let regex = EdgeRegex::parse("(a|b)* c? . _").unwrap();
+    assert!(regex.matches(["a", "b", "a", "c", "x"]));
+    assert!(regex.matches(["z"]));
+    assert!(!regex.matches(["c", "c", "c"]));
+    assert!(!regex.matches([]));
+
+    let regex = EdgeRegex::parse("a{2,}").unwrap();
+    assert!(!regex.matches(["a"]));
+    assert!(regex.matches(["a", "a", "a", "a", "a"]));
+
+    assert!(EdgeRegex::parse("a{3,1}").is_err());
+    assert!(EdgeRegex::parse("(a|b").is_err());
+    assert_eq!(EdgeRegex::parse("a||b").unwrap_err().position, 2);
+}
+
+#[test]
+fn regular_simulation_example() {
+    let pattern = RegularGraph::build(&[(0, "P"), (1, "C")], &[(0, 1, "friend{1,3}·colleague")]);
+    let data = RegularGraph::build(
+        &[(10, "P"), (11, "P"), (12, "P"), (13, "C"), (14, "P"), (15, "C")],
+        &[(10, 11, "friend"), (11, 12, "friend"), (12, 13, "colleague"), (14, 15, "colleague")],
+    );
+    let sim = ids(&pattern.get_regular_simulation(&data).unwrap());
+    assert_eq!(sim[&0], HashSet::from([10, 11]));
+    assert_eq!(sim[&1], HashSet::from([13, 15]));
+}
+
+#[test]
+fn regular_simulation_same() {
+    let regexes = ["a", "b", "_", "a·b", "a{1,2}", "(a|b)·a?", "b{2}|a·_·b", "a? b"];
+    let mut rng = Pcg64::seed_from_u64(11);
+    for _ in 0..100 {
+        let labels = ["0", "1"];
+        let pattern_nodes: Vec<(u64, &str)> = (0..3).map(|u| (u, labels[rng.random_range(0..2)])).collect();
+        let pattern_edges: Vec<(u64, u64, &str)> = (0..3).map(|_| {
+            (rng.random_range(0..3), rng.random_range(0..3), regexes[rng.random_range(0..regexes.len())])
+        }).collect();
+        let data_nodes: Vec<(u64, &str)> = (0..10).map(|v| (v, labels[rng.random_range(0..2)])).collect();
+        let data_edges: Vec<(u64, u64, &str)> = (0..16).map(|_| {
+            (rng.random_range(0..10), rng.random_range(0..10), ["a", "b", "c"][rng.random_range(0..3)])
+        }).collect();
+        let pattern = RegularGraph::build(&pattern_nodes, &pattern_edges);
+        let data = RegularGraph::build(&data_nodes, &data_edges);
+
+        assert_eq!(ids(&pattern.get_regular_simulation(&data).unwrap()), naive_regular_simulation(&pattern, &data, 3));
+    }
+}
+
+#[test]
+fn regular_simulation_invalid() {
+    let pattern = RegularGraph::build(&[(0, "P"), (1, "C")], &[(0, 1, "friend{3,1}")]);
+    let data = RegularGraph::build(&[(10, "P"), (11, "C")], &[(10, 11, "friend")]);
+    let Err(error) = pattern.get_regular_simulation(&data) else {
+        panic!("expected an invalid regular expression");
+    };
+    assert_eq!(error, EdgeRegex::parse("friend{3,1}").unwrap_err());
+}
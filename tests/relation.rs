@@ -0,0 +1,60 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::build_graph;
+use graph_base::impls::standard::{LabelNode, StandardLabeledGraph};
+use graph_base::interfaces::graph::{Graph, SingleId};
+use graph_simulation::algorithm::relation::SimulationRelation;
+use graph_simulation::algorithm::simulation::Simulation;
+
+fn node(graph: &StandardLabeledGraph, id: usize) -> &LabelNode<String> {
+    graph.nodes().find(|v| v.id() == id).unwrap()
+}
+
+#[test]
+fn relation_queries() {
+    let pattern = build_graph(&[(0, "A"), (1, "B")], &[(0, 1)]);
+    let data = build_graph(&[(1, "A"), (2, "B"), (3, "A"), (4, "B")], &[(1, 2), (3, 2)]);
+
+    let relation = pattern.get_simulation_relation(&data);
+    let (u0, u1) = (node(&pattern, 0), node(&pattern, 1));
+    assert!(relation.is_total());
+    assert_eq!(relation.len(), 4);
+    assert!(relation.contains(u0, node(&data, 3)));
+    assert!(!relation.contains(u0, node(&data, 2)));
+    assert_eq!(relation.matches_of(u0).map(|v| v.id()).collect::<HashSet<_>>(), HashSet::from([1, 3]));
+    assert!(relation.inverse_matches_of(node(&data, 4)).collect::<Vec<_>>() == vec![u1]);
+    assert_eq!(relation.pairs().count(), 4);
+
+    // Algorithms computing the same relation compare equal, and the conversion back is lossless.
+    assert!(relation == SimulationRelation::from(pattern.get_simulation_native(&data)), "{}", relation);
+    assert!(relation == SimulationRelation::from(pattern.get_simulation_inter_dense(&data)), "{}", relation);
+    let map: HashMap<_, _> = relation.clone().into();
+    assert!(map == pattern.get_simulation_hhk(&data));
+    assert!(pattern.get_dual_simulation_relation(&data) == SimulationRelation::from(pattern.get_dual_simulation(&data)));
+}
+
+#[test]
+fn relation_set_operations() {
+    let pattern = build_graph(&[(0, "A"), (1, "B")], &[(0, 1)]);
+    let data = build_graph(&[(1, "A"), (2, "B"), (3, "A"), (4, "B")], &[(1, 2), (3, 2)]);
+    let (u0, u1) = (node(&pattern, 0), node(&pattern, 1));
+    let (v1, v2, v3, v4) = (node(&data, 1), node(&data, 2), node(&data, 3), node(&data, 4));
+
+    let left: SimulationRelation<_> = [(u0, v1), (u1, v2)].into_iter().collect();
+    let right: SimulationRelation<_> = [(u0, v3), (u1, v2), (u1, v4)].into_iter().collect();
+
+    assert!(left.union(&right) == [(u0, v1), (u0, v3), (u1, v2), (u1, v4)].into_iter().collect());
+    assert!(left.intersection(&right) == [(u1, v2)].into_iter().collect());
+    assert!(left.difference(&right) == [(u0, v1)].into_iter().collect());
+    assert!(left.intersection(&right).is_subset(&left));
+    assert!(!left.is_subset(&right));
+
+    // The empty relation of a failed match is neither total nor equal to a non-empty one.
+    let mut empty = left.difference(&left);
+    assert!(empty.is_empty() && !empty.is_total());
+    assert!(empty != left);
+    assert!(empty.insert(u0, v1) && !empty.insert(u0, v1));
+    assert!(empty.remove(u0, v1) && empty.is_empty());
+}
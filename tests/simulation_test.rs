@@ -1,8 +1,9 @@
 use std::collections::{HashMap, HashSet};
 use std::env;
 
-use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::impls::standard::{LabelNode, StandardLabeledGraph};
 use graph_base::interfaces::graph::Graph;
+use graph_base::interfaces::labeled::Labeled;
 use graph_simulation::algorithm::simulation::Simulation;
 use std::fs;
 
@@ -64,7 +65,7 @@ fn test_simulation() {
                     }
 
                     let sim = graph1.get_simulation_inter(&graph2);
-                    let has_sim=  StandardLabeledGraph::has_simulation(sim);
+                    let has_sim=  StandardLabeledGraph::has_simulation(&sim);
 
                     match (is_true, has_sim) {
                         (true, true) => assert!(true),
@@ -153,4 +154,121 @@ fn simulation_same() {
                 }
             }
         }
-}
\ No newline at end of file
+}
+
+fn load_graphs(content: &str) -> (bool, StandardLabeledGraph, StandardLabeledGraph) {
+    let mut lines = content.split_whitespace();
+    let is_true = lines.next().unwrap() == "t";
+    let mut graphs = Vec::new();
+    for _ in 0..2 {
+        let n: usize = lines.next().unwrap().parse().unwrap();
+        let m: usize = lines.next().unwrap().parse().unwrap();
+        let _: usize = lines.next().unwrap().parse().unwrap();
+
+        let mut graph = StandardLabeledGraph::new();
+        for _ in 0..n {
+            let node: u64 = lines.next().unwrap().parse().unwrap();
+            let label: String = lines.next().unwrap().parse().unwrap();
+            graph.add_node(node, label);
+        }
+        for _ in 0..m {
+            let source: u64 = lines.next().unwrap().parse().unwrap();
+            let destination: u64 = lines.next().unwrap().parse().unwrap();
+            graph.add_edge(source, destination);
+        }
+        graphs.push(graph);
+    }
+    let graph2 = graphs.pop().unwrap();
+    let graph1 = graphs.pop().unwrap();
+    (is_true, graph1, graph2)
+}
+
+fn simulation_test_files() -> Vec<(String, String)> {
+    let paths = fs::read_dir(format!("{}/data/label_graph/simulation_test/", env!("CARGO_MANIFEST_DIR"))).expect("Unable to read directory");
+    paths.filter_map(|path| path.ok()).map(|entry| entry.path()).filter(|path| path.is_file()).map(|path| {
+        let graph_name = path.file_name().unwrap().to_str().unwrap().to_string();
+        let content = fs::read_to_string(&path).expect("Unable to read file");
+        (graph_name, content)
+    }).collect()
+}
+
+/// Naive (dual) simulation: drop (u, v) while some pattern edge at u has no counterpart at v.
+fn naive_simulation<'a>(graph1: &'a StandardLabeledGraph, graph2: &'a StandardLabeledGraph, dual: bool) -> HashMap<&'a LabelNode<String>, HashSet<&'a LabelNode<String>>> {
+    let edges1: Vec<_> = graph1.get_edges_pair().collect();
+    let (mut post2, mut pre2): (HashMap<_, HashSet<_>>, HashMap<_, HashSet<_>>) = (HashMap::new(), HashMap::new());
+    for (x, y) in graph2.get_edges_pair() {
+        post2.entry(x).or_default().insert(y);
+        pre2.entry(y).or_default().insert(x);
+    }
+
+    let mut expected: HashMap<_, HashSet<_>> = graph1.nodes().map(|u| {
+        (u, graph2.nodes().filter(|v| graph1.label_same(u, v)).collect())
+    }).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &(u, u_prime) in &edges1 {
+            let drop_u: Vec<_> = expected[u].iter().copied().filter(|v| post2.get(v).is_none_or(|ys| ys.is_disjoint(&expected[u_prime]))).collect();
+            changed |= !drop_u.is_empty();
+            for v in drop_u {
+                expected.get_mut(u).unwrap().remove(v);
+            }
+            if dual {
+                let drop_u_prime: Vec<_> = expected[u_prime].iter().copied().filter(|v| pre2.get(v).is_none_or(|xs| xs.is_disjoint(&expected[u]))).collect();
+                changed |= !drop_u_prime.is_empty();
+                for v in drop_u_prime {
+                    expected.get_mut(u_prime).unwrap().remove(v);
+                }
+            }
+        }
+    }
+    expected
+}
+
+#[test]
+fn dual_simulation_same() {
+    for (graph_name, content) in simulation_test_files() {
+        let (is_true, graph1, graph2) = load_graphs(&content);
+        let expected = naive_simulation(&graph1, &graph2, true);
+
+        let dual = graph1.get_dual_simulation(&graph2);
+        for u in graph1.nodes() {
+            assert!(dual[u] == expected[u], "{}: dual simulation differs at {}", graph_name, u);
+        }
+        if is_true {
+            assert!(StandardLabeledGraph::has_simulation(&dual), "{}: expected a dual simulation", graph_name);
+        }
+    }
+}
+
+#[test]
+fn simulation_hhk_same() {
+    for (graph_name, content) in simulation_test_files() {
+        let (_, graph1, graph2) = load_graphs(&content);
+        let expected = naive_simulation(&graph1, &graph2, false);
+
+        let sim = graph1.get_simulation_hhk(&graph2);
+        for u in graph1.nodes() {
+            assert!(sim[u] == expected[u], "{}: hhk simulation differs at {}", graph_name, u);
+        }
+    }
+}
+
+#[test]
+fn dense_simulation_same() {
+    for (graph_name, content) in simulation_test_files() {
+        let (_, graph1, graph2) = load_graphs(&content);
+        let expected = naive_simulation(&graph1, &graph2, false);
+
+        let (inter, native, node_edge) = (
+            graph1.get_simulation_inter_dense(&graph2),
+            graph1.get_simulation_native_dense(&graph2),
+            graph1.get_simulation_of_node_edge_dense(&graph2),
+        );
+        for u in graph1.nodes() {
+            assert!(inter[u] == expected[u], "{}: dense inter simulation differs at {}", graph_name, u);
+            assert!(native[u] == expected[u], "{}: dense native simulation differs at {}", graph_name, u);
+            assert!(node_edge[u] == expected[u], "{}: dense node-edge simulation differs at {}", graph_name, u);
+        }
+    }
+}
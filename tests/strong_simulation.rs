@@ -0,0 +1,37 @@
+mod common;
+
+use common::build_graph;
+use graph_base::impls::standard::StandardLabeledGraph;
+use graph_base::interfaces::graph::SingleId;
+use graph_simulation::algorithm::simulation::Simulation;
+use graph_simulation::algorithm::strong::StrongSimulation;
+
+#[test]
+fn strong_simulation_chain() {
+    let pattern = build_graph(&[(0, "A"), (1, "B"), (2, "C")], &[(0, 1), (1, 2)]);
+    let data = build_graph(
+        &[(1, "A"), (2, "B"), (3, "C"), (4, "A"), (5, "B"), (6, "B"), (7, "C")],
+        &[(1, 2), (2, 3), (4, 5), (6, 7)],
+    );
+
+    assert_eq!(pattern.get_diameter(), 2);
+
+    let matches = pattern.get_strong_simulation(&data);
+    assert_eq!(matches.len(), 1);
+
+    let mut nodes: Vec<usize> = matches[0].nodes().iter().map(|v| v.id()).collect();
+    nodes.sort();
+    assert_eq!(nodes, vec![1, 2, 3]);
+    assert_eq!(matches[0].edges().len(), 2);
+}
+
+#[test]
+fn strong_simulation_is_local() {
+    // The alternating 4-cycle is a dual simulation of the 2-cycle, but no ball of radius 1 contains a cycle.
+    let pattern = build_graph(&[(0, "A"), (1, "B")], &[(0, 1), (1, 0)]);
+    let data = build_graph(&[(1, "A"), (2, "B"), (3, "A"), (4, "B")], &[(1, 2), (2, 3), (3, 4), (4, 1)]);
+
+    assert_eq!(pattern.get_diameter(), 1);
+    assert!(StandardLabeledGraph::has_simulation(&pattern.get_dual_simulation(&data)));
+    assert!(pattern.get_strong_simulation(&data).is_empty());
+}
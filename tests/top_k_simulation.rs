@@ -0,0 +1,68 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{build_graph, random_graph};
+use graph_base::interfaces::graph::{Adjacency, Graph, SingleId};
+use graph_simulation::algorithm::simulation::Simulation;
+use graph_simulation::algorithm::topk::{Score, TopKSimulation};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+#[test]
+fn top_k_same() {
+    let mut rng = Pcg64::seed_from_u64(16);
+    for _ in 0..100 {
+        let pattern = random_graph(&mut rng, 4, 5, 2);
+        let data = random_graph(&mut rng, 16, 30, 2);
+        let adj = data.get_adj();
+        let degree = |v| data.get_post(&adj, v).count();
+        let sim = pattern.get_simulation_hhk(&data);
+        for k in [0, 1, 3, 16] {
+            // By degree, then by the greater id.
+            let top_k = pattern.get_simulation_top_k(&data, k, |_, v| (degree(v), v.id()));
+            for u in pattern.nodes() {
+                let mut expected: Vec<_> = sim[u].iter().copied().collect();
+                expected.sort_by_key(|&v| std::cmp::Reverse((degree(v), v.id())));
+                expected.truncate(k);
+                assert!(top_k[u] == expected);
+            }
+
+            // A float score, where NaN ranks above every number.
+            let score = |v| {
+                let degree = degree(v) as f64;
+                Score(if v.id() % 7 == 0 { f64::NAN } else { degree + v.id() as f64 / 100.0 })
+            };
+            let top_k = pattern.get_simulation_top_k(&data, k, |_, v| score(v));
+            for u in pattern.nodes() {
+                let mut expected: Vec<_> = sim[u].iter().copied().collect();
+                expected.sort_by_key(|&v| std::cmp::Reverse((score(v), v.id())));
+                expected.truncate(k);
+                assert!(top_k[u].iter().map(|&v| score(v)).eq(expected.iter().map(|&v| score(v))));
+            }
+        }
+    }
+}
+
+#[test]
+fn top_k_example() {
+    // A long chain of A nodes where only the last one misses a B child, and a short one that matches.
+    let pattern = build_graph(&[(0, "A"), (1, "B")], &[(0, 0), (0, 1)]);
+    let mut nodes: Vec<(u64, &str)> = (0..100).map(|v| (v, "A")).collect();
+    nodes.extend((100..200).map(|v| (v, "B")));
+    nodes.extend([(200, "A"), (201, "B")]);
+    let mut edges: Vec<(u64, u64)> = (0..99).map(|v| (v, v + 1)).collect();
+    edges.extend((0..99).map(|v| (v, v + 100)));
+    edges.extend([(200, 200), (200, 201)]);
+    let data = build_graph(&nodes, &edges);
+
+    // The chain ranks first, and is ruled out pair by pair before the short match is verified.
+    let top_k = pattern.get_simulation_top_k(&data, 1, |_, v| std::cmp::Reverse(v.id()));
+    let ids = |u: usize| top_k.iter().find(|(x, _)| x.id() == u).unwrap().1.iter().map(|v| v.id()).collect::<Vec<_>>();
+    assert_eq!(ids(0), vec![200]);
+    assert_eq!(ids(1), vec![100]);
+
+    // The chain is not a match at all, since its last node has no B child.
+    let sim = pattern.get_simulation_hhk(&data);
+    assert_eq!(sim.iter().find(|(x, _)| x.id() == 0).unwrap().1.iter().map(|v| v.id()).collect::<HashSet<_>>(), HashSet::from([200]));
+}